@@ -0,0 +1,293 @@
+use stream::Stream;
+use stream::StreamCell::{Cons, Nil};
+
+pub trait Deque<T> {
+    fn empty() -> Self;
+    fn is_empty(&self) -> bool;
+
+    fn cons(&self, T) -> Self;
+    fn head(&self) -> Option<T>;
+    fn tail(&self) -> Option<Self> where Self: Sized;
+
+    fn snoc(&self, T) -> Self;
+    fn last(&self) -> Option<T>;
+    fn init(&self) -> Option<Self> where Self: Sized;
+}
+
+// neither stream is ever allowed to be more than C times longer than the other one (plus one)
+const C: usize = 3;
+
+// Section 8.4.2:
+// O(1) amortized time for every operation, even when used persistently
+#[derive(Clone)]
+pub struct BankersDeque<T> {
+    lenf: usize,
+    front: Stream<T>,
+    lenr: usize,
+    rear: Stream<T>
+}
+
+// moves half of the elements of the longer stream onto the shorter one when they are unbalanced
+fn bankers<T: Clone + 'static>(lenf: usize, f: Stream<T>, lenr: usize, r: Stream<T>) -> BankersDeque<T> {
+    if lenf > C * lenr + 1 {
+        let i = (lenf + lenr) / 2;
+        let j = lenf + lenr - i;
+
+        BankersDeque { lenf: i, front: f.take(i), lenr: j, rear: r.append(&f.drop(i).reverse()) }
+    } else if lenr > C * lenf + 1 {
+        let j = (lenf + lenr) / 2;
+        let i = lenf + lenr - j;
+
+        BankersDeque { lenf: i, front: f.append(&r.drop(j).reverse()), lenr: j, rear: r.take(j) }
+    } else {
+        BankersDeque { lenf: lenf, front: f, lenr: lenr, rear: r }
+    }
+}
+
+impl<T: Clone + 'static> Deque<T> for BankersDeque<T> {
+    fn empty() -> BankersDeque<T> {
+        BankersDeque { lenf: 0, front: Stream::empty(), lenr: 0, rear: Stream::empty() }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.lenf + self.lenr == 0
+    }
+
+    fn cons(&self, x: T) -> BankersDeque<T> {
+        bankers(self.lenf + 1, self.front.cons(x), self.lenr, self.rear.clone())
+    }
+
+    // when one of the streams is empty the other one holds at most one element
+    fn head(&self) -> Option<T> {
+        match *self.front.force() {
+            Cons(ref x, _) => Some(x.clone()),
+            Nil => self.rear.head()
+        }
+    }
+
+    fn tail(&self) -> Option<BankersDeque<T>> {
+        match *self.front.force() {
+            Cons(_, ref f) => Some(bankers(self.lenf - 1, f.clone(), self.lenr, self.rear.clone())),
+            Nil => if self.rear.is_empty() { None } else { Some(Deque::empty()) }
+        }
+    }
+
+    fn snoc(&self, x: T) -> BankersDeque<T> {
+        bankers(self.lenf, self.front.clone(), self.lenr + 1, self.rear.cons(x))
+    }
+
+    fn last(&self) -> Option<T> {
+        match *self.rear.force() {
+            Cons(ref x, _) => Some(x.clone()),
+            Nil => self.front.head()
+        }
+    }
+
+    fn init(&self) -> Option<BankersDeque<T>> {
+        match *self.rear.force() {
+            Cons(_, ref r) => Some(bankers(self.lenf, self.front.clone(), self.lenr - 1, r.clone())),
+            Nil => if self.front.is_empty() { None } else { Some(Deque::empty()) }
+        }
+    }
+}
+
+// Section 8.4.3:
+// O(1) worst-case time for every operation. Rebalancing is done by incremental rotations, and
+// every operation forces one or two more cells of them through the schedules.
+#[derive(Clone)]
+pub struct RealTimeDeque<T> {
+    lenf: usize,
+    front: Stream<T>,
+    sf: Stream<T>,
+    lenr: usize,
+    rear: Stream<T>,
+    sr: Stream<T>
+}
+
+fn exec1<T: Clone + 'static>(s: &Stream<T>) -> Stream<T> {
+    match s.tail() {
+        Some(s) => s,
+        None => s.clone()
+    }
+}
+
+fn exec2<T: Clone + 'static>(s: &Stream<T>) -> Stream<T> {
+    exec1(&exec1(s))
+}
+
+// computes f ++ reverse r ++ a, reversing C elements of r for every element of f
+fn rotate_rev<T: Clone + 'static>(f: Stream<T>, r: Stream<T>, a: Stream<T>) -> Stream<T> {
+    Stream::lazy(move || {
+        match *f.force() {
+            Cons(ref x, ref f1) =>
+                Cons(x.clone(), rotate_rev(f1.clone(), r.drop(C), r.take(C).reverse().append(&a))),
+            Nil => {
+                let s = r.reverse().append(&a);
+                let c = s.force().clone();
+                c
+            }
+        }
+    })
+}
+
+// computes f ++ reverse (drop j r), dropping C elements of r for every element of f
+fn rotate_drop<T: Clone + 'static>(f: Stream<T>, j: usize, r: Stream<T>) -> Stream<T> {
+    if j < C {
+        return rotate_rev(f, r.drop(j), Stream::empty());
+    }
+
+    Stream::lazy(move || {
+        match *f.force() {
+            Cons(ref x, ref f1) => Cons(x.clone(), rotate_drop(f1.clone(), j - C, r.drop(C))),
+            Nil => panic!("front stream shorter than expected")
+        }
+    })
+}
+
+fn real_time<T: Clone + 'static>(lenf: usize, f: Stream<T>, sf: Stream<T>,
+                                 lenr: usize, r: Stream<T>, sr: Stream<T>) -> RealTimeDeque<T> {
+    if lenf > C * lenr + 1 {
+        let i = (lenf + lenr) / 2;
+        let j = lenf + lenr - i;
+        let f1 = f.take(i);
+        let r1 = rotate_drop(r, i, f);
+
+        RealTimeDeque { lenf: i, front: f1.clone(), sf: f1, lenr: j, rear: r1.clone(), sr: r1 }
+    } else if lenr > C * lenf + 1 {
+        let j = (lenf + lenr) / 2;
+        let i = lenf + lenr - j;
+        let r1 = r.take(j);
+        let f1 = rotate_drop(f, j, r);
+
+        RealTimeDeque { lenf: i, front: f1.clone(), sf: f1, lenr: j, rear: r1.clone(), sr: r1 }
+    } else {
+        RealTimeDeque { lenf: lenf, front: f, sf: sf, lenr: lenr, rear: r, sr: sr }
+    }
+}
+
+impl<T: Clone + 'static> Deque<T> for RealTimeDeque<T> {
+    fn empty() -> RealTimeDeque<T> {
+        RealTimeDeque {
+            lenf: 0, front: Stream::empty(), sf: Stream::empty(),
+            lenr: 0, rear: Stream::empty(), sr: Stream::empty()
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.lenf + self.lenr == 0
+    }
+
+    fn cons(&self, x: T) -> RealTimeDeque<T> {
+        real_time(self.lenf + 1, self.front.cons(x), exec1(&self.sf),
+                  self.lenr, self.rear.clone(), exec1(&self.sr))
+    }
+
+    // when one of the streams is empty the other one holds at most one element
+    fn head(&self) -> Option<T> {
+        match *self.front.force() {
+            Cons(ref x, _) => Some(x.clone()),
+            Nil => self.rear.head()
+        }
+    }
+
+    fn tail(&self) -> Option<RealTimeDeque<T>> {
+        match *self.front.force() {
+            Cons(_, ref f) =>
+                Some(real_time(self.lenf - 1, f.clone(), exec2(&self.sf),
+                               self.lenr, self.rear.clone(), exec2(&self.sr))),
+            Nil => if self.rear.is_empty() { None } else { Some(Deque::empty()) }
+        }
+    }
+
+    fn snoc(&self, x: T) -> RealTimeDeque<T> {
+        real_time(self.lenf, self.front.clone(), exec1(&self.sf),
+                  self.lenr + 1, self.rear.cons(x), exec1(&self.sr))
+    }
+
+    fn last(&self) -> Option<T> {
+        match *self.rear.force() {
+            Cons(ref x, _) => Some(x.clone()),
+            Nil => self.front.head()
+        }
+    }
+
+    fn init(&self) -> Option<RealTimeDeque<T>> {
+        match *self.rear.force() {
+            Cons(_, ref r) =>
+                Some(real_time(self.lenf, self.front.clone(), exec2(&self.sf),
+                               self.lenr - 1, r.clone(), exec2(&self.sr))),
+            Nil => if self.front.is_empty() { None } else { Some(Deque::empty()) }
+        }
+    }
+}
+
+#[cfg(test)]
+fn double_ended<D: Deque<usize> + Clone>() {
+    let d: D = Deque::empty();
+
+    assert!(d.is_empty());
+    assert_eq!(d.head(), None);
+    assert_eq!(d.last(), None);
+    assert!(d.tail().is_none());
+    assert!(d.init().is_none());
+
+    let d1 = (0..100).fold(d.clone(), |d, x| d.snoc(x));
+
+    assert_eq!(d1.head(), Some(0));
+    assert_eq!(d1.last(), Some(99));
+
+    // draining from the opposite end forces the deque to rebalance
+    let mut d2 = d1.clone();
+
+    for x in 0..100 {
+        assert_eq!(d2.head(), Some(x));
+        assert_eq!(d2.last(), Some(99));
+        d2 = d2.tail().unwrap();
+    }
+
+    assert!(d2.is_empty());
+
+    let d3 = (0..100).fold(d.clone(), |d, x| d.cons(x));
+    let mut d4 = d3.clone();
+
+    for x in 0..100 {
+        assert_eq!(d4.last(), Some(x));
+        assert_eq!(d4.head(), Some(99));
+        d4 = d4.init().unwrap();
+    }
+
+    assert!(d4.is_empty());
+
+    // mixing both ends, checked against a vector
+    let mut d5 = d.clone();
+    let mut v = vec![];
+
+    for (i, x) in ::random(1000).take(1000).enumerate() {
+        match x % 4 {
+            0 => { d5 = d5.cons(i); v.insert(0, i); },
+            1 => { d5 = d5.snoc(i); v.push(i); },
+            2 => if !v.is_empty() { d5 = d5.tail().unwrap(); v.remove(0); },
+            _ => if !v.is_empty() { d5 = d5.init().unwrap(); v.pop(); }
+        }
+
+        assert_eq!(d5.head(), v.first().cloned());
+        assert_eq!(d5.last(), v.last().cloned());
+        assert_eq!(d5.is_empty(), v.is_empty());
+    }
+
+    // older versions are unaffected
+    assert_eq!(d1.head(), Some(0));
+    assert_eq!(d1.last(), Some(99));
+    assert_eq!(d3.head(), Some(99));
+    assert_eq!(d3.last(), Some(0));
+}
+
+#[test]
+fn bankers_deque() {
+    double_ended::<BankersDeque<usize>>();
+}
+
+#[test]
+fn real_time_deque() {
+    double_ended::<RealTimeDeque<usize>>();
+}
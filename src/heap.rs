@@ -1,56 +1,164 @@
-use std::collections::VecDeque;
+use std::cmp::Ordering;
 use std::fmt::{Display, Error, Formatter};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
 use std::rc::Rc;
 
-pub trait Heap<T: Ord> {
+use order::{Natural, Order};
+use stack::{List, Stack};
+use stack::ListNode::{Cons, Nil};
+
+pub trait Heap<T> {
     fn empty() -> Self;
     fn is_empty(&self) -> bool;
 
     fn merge(&self, &Self) -> Self;
     fn insert(&self, T) -> Self;
 
-    fn find_min(&self) -> T;
-    fn delete_min(&self) -> Self;
+    fn find_min(&self) -> Option<T>;
+    fn delete_min(&self) -> Option<Self> where Self: Sized;
+
+    fn pop_min(&self) -> Option<(T, Self)> where Self: Sized {
+        match (self.find_min(), self.delete_min()) {
+            (Some(x), Some(h)) => Some((x, h)),
+            _ => None
+        }
+    }
+
+    fn iter(&self) -> SortedDrain<T, Self> where Self: Clone + Sized {
+        SortedDrain { heap: self.clone(), marker: PhantomData }
+    }
+
+    fn into_sorted_vec(self) -> Vec<T> where Self: Sized {
+        SortedDrain { heap: self, marker: PhantomData }.collect()
+    }
+
+    // Exercise 3.3:
+    // merges singleton heaps in pairs, then the resulting heaps in pairs, and so on until only one
+    // is left. That takes O(n) time when merge takes O(log n) time, as there are half as many
+    // merges in every round, each of them on heaps twice as big.
+    fn build<I: IntoIterator<Item = T>>(xs: I) -> Self where Self: Sized {
+        let mut hs: Vec<Self> = xs.into_iter().map(|x| Self::empty().insert(x)).collect();
+
+        while hs.len() > 1 {
+            let mut merged = Vec::with_capacity(hs.len() / 2 + 1);
+            let mut iter = hs.into_iter();
+
+            while let Some(h1) = iter.next() {
+                merged.push(match iter.next() {
+                    Some(h2) => h1.merge(&h2),
+                    None => h1
+                });
+            }
+
+            hs = merged;
+        }
+
+        hs.pop().unwrap_or_else(Self::empty)
+    }
+}
+
+/// Sorts the elements in O(n log n) time by building a heap of type `H` out of them and draining
+/// it.
+pub fn heap_sort<H: Heap<T>, T>(xs: Vec<T>) -> Vec<T> {
+    H::build(xs).into_sorted_vec()
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum LeftistHeap<T> {
-    Tip,
-    Node(usize, T, Rc<LeftistHeap<T>>, Rc<LeftistHeap<T>>),
+/// Iterator that yields the elements of a heap in ascending order by repeatedly deleting its
+/// minimum.
+pub struct SortedDrain<T, H> {
+    heap: H,
+    marker: PhantomData<T>
+}
+
+impl<T, H: Heap<T>> Iterator for SortedDrain<T, H> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match self.heap.pop_min() {
+            Some((x, h)) => {
+                self.heap = h;
+                Some(x)
+            },
+            None => None
+        }
+    }
+}
+
+macro_rules! heap_iterators {
+    ($heap: ident<$($param: ident),+> where $($bounds: tt)+) => {
+        impl<$($param),+> IntoIterator for $heap<$($param),+> where $($bounds)+ {
+            type Item = T;
+            type IntoIter = SortedDrain<T, $heap<$($param),+>>;
+
+            fn into_iter(self) -> SortedDrain<T, $heap<$($param),+>> {
+                SortedDrain { heap: self, marker: PhantomData }
+            }
+        }
+
+        impl<$($param),+> FromIterator<T> for $heap<$($param),+> where $($bounds)+ {
+            fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> $heap<$($param),+> {
+                Heap::build(iter)
+            }
+        }
+
+        impl<$($param),+> Extend<T> for $heap<$($param),+> where $($bounds)+ {
+            fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+                *self = self.merge(&Heap::build(iter));
+            }
+        }
+    }
+}
+
+// the tip carries the ordering of the heap, as a type parameter that is only used recursively is
+// rejected
+#[derive(Debug, Eq, PartialEq)]
+pub enum LeftistHeap<T, O = Natural> {
+    Tip(PhantomData<O>),
+    Node(usize, T, Rc<LeftistHeap<T, O>>, Rc<LeftistHeap<T, O>>),
 }
 
 use heap::LeftistHeap::{Tip, Node};
 
-impl<T: Ord + Clone> Heap<T> for LeftistHeap<T> {
-    fn empty() -> LeftistHeap<T> {
-        Tip
+impl<T: Clone, O> Clone for LeftistHeap<T, O> {
+    fn clone(&self) -> LeftistHeap<T, O> {
+        match *self {
+            Tip(_) => Tip(PhantomData),
+            Node(r, ref x, ref a, ref b) => Node(r, x.clone(), a.clone(), b.clone())
+        }
+    }
+}
+
+impl<T: Clone, O: Order<T>> Heap<T> for LeftistHeap<T, O> {
+    fn empty() -> LeftistHeap<T, O> {
+        Tip(PhantomData)
     }
 
     fn is_empty(&self) -> bool {
         match *self {
-            Tip => true,
+            Tip(_) => true,
             _ => false
         }
     }
 
-    fn merge(&self, h: &LeftistHeap<T>) -> LeftistHeap<T> {
-        fn rank<T>(h: &LeftistHeap<T>) -> usize {
+    fn merge(&self, h: &LeftistHeap<T, O>) -> LeftistHeap<T, O> {
+        fn rank<T, O>(h: &LeftistHeap<T, O>) -> usize {
             match *h {
-                Tip => 0,
+                Tip(_) => 0,
                 Node(r, _, _, _) => r
             }
         }
 
-        fn make_node<T: Clone>(x: T, l: Rc<LeftistHeap<T>>, r: Rc<LeftistHeap<T>>) -> LeftistHeap<T> {
+        fn make_node<T: Clone, O>(x: T, l: Rc<LeftistHeap<T, O>>, r: Rc<LeftistHeap<T, O>>) -> LeftistHeap<T, O> {
             if rank(&l) >= rank(&r) { Node(rank(&r) + 1, x.clone(), l.clone(), r.clone()) }
             else { Node(rank(&l) + 1, x.clone(), r.clone(), l.clone()) }
         }
 
         match (self, h) {
-            (e, &Tip) => e.clone(),
-            (&Tip, e) => e.clone(),
+            (e, &Tip(_)) => e.clone(),
+            (&Tip(_), e) => e.clone(),
             (&Node(_, ref x, ref l1, ref r1), &Node(_, ref y, ref l2, ref r2)) => {
-                if *x <= *y {
+                if O::leq(x, y) {
                     make_node(x.clone(), l1.clone(), Rc::new(r1.merge(h)))
                 } else {
                     make_node(y.clone(), l2.clone(), Rc::new(self.merge(r2)))
@@ -59,29 +167,31 @@ impl<T: Ord + Clone> Heap<T> for LeftistHeap<T> {
         }
     }
 
-    fn insert(&self, x: T) -> LeftistHeap<T> {
-        let h = Node(1, x, Rc::new(Tip), Rc::new(Tip));
+    fn insert(&self, x: T) -> LeftistHeap<T, O> {
+        let h = Node(1, x, Rc::new(Heap::empty()), Rc::new(Heap::empty()));
         h.merge(self)
     }
 
-    fn find_min(&self) -> T {
+    fn find_min(&self) -> Option<T> {
         match *self {
-            Tip => panic!("empty heap"),
-            Node(_, ref x, _, _) => x.clone()
+            Tip(_) => None,
+            Node(_, ref x, _, _) => Some(x.clone())
         }
     }
 
-    fn delete_min(&self) -> LeftistHeap<T> {
+    fn delete_min(&self) -> Option<LeftistHeap<T, O>> {
         match *self {
-            Tip => panic!("empty heap"),
-            Node(_, _, ref l, ref r) => l.merge(r)
+            Tip(_) => None,
+            Node(_, _, ref l, ref r) => Some(l.merge(r))
         }
     }
 }
 
-impl<T: Display> Display for LeftistHeap<T> {
+heap_iterators!(LeftistHeap<T, O> where T: Clone, O: Order<T>);
+
+impl<T: Display, O> Display for LeftistHeap<T, O> {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        fn aux<T: Display>(f: &mut Formatter, t: &LeftistHeap<T>, right: bool, indent: &str) -> Result<(), Error> {
+        fn aux<T: Display, O>(f: &mut Formatter, t: &LeftistHeap<T, O>, right: bool, indent: &str) -> Result<(), Error> {
             match *t {
                 Node(ref rank, ref x, ref l, ref r) => {
                     try!(aux(f, r, true, &(indent.to_string() + if right { "        " } else { " |      " })));
@@ -94,7 +204,7 @@ impl<T: Display> Display for LeftistHeap<T> {
 
                     aux(f, l, false, &(indent.to_string() + if right { " |      " } else { "        " }))
                 },
-                Tip => {
+                Tip(_) => {
                     try!(write!(f, "{}", indent));
                     try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
                     try!(write!(f, "{}", "----- "));
@@ -110,36 +220,159 @@ impl<T: Display> Display for LeftistHeap<T> {
                 try!(writeln!(f, "(#{}, {})", rank, x));
                 aux(f, l, false, "")
             },
-            Tip => Result::Ok(())
+            Tip(_) => Result::Ok(())
+        }
+    }
+}
+
+// Exercise 3.4:
+// a leftist heap that keeps the size of every subtree instead of the length of its right spine.
+// The size of a merged heap is known before merging, so merge can decide which child goes on the
+// left before recursing and runs in a single top-down pass.
+#[derive(Debug, Eq, PartialEq)]
+pub enum WeightBiasedLeftistHeap<T, O = Natural> {
+    Tip(PhantomData<O>),
+    Node(usize, T, Rc<WeightBiasedLeftistHeap<T, O>>, Rc<WeightBiasedLeftistHeap<T, O>>),
+}
+
+impl<T: Clone, O> Clone for WeightBiasedLeftistHeap<T, O> {
+    fn clone(&self) -> WeightBiasedLeftistHeap<T, O> {
+        match *self {
+            WeightBiasedLeftistHeap::Tip(_) => WeightBiasedLeftistHeap::Tip(PhantomData),
+            WeightBiasedLeftistHeap::Node(w, ref x, ref a, ref b) => WeightBiasedLeftistHeap::Node(w, x.clone(), a.clone(), b.clone())
         }
     }
 }
 
-macro_rules! vecdeque {
-    ($( $v: expr ),*) => {{
-         let mut vec = ::std::collections::VecDeque::new();
-         $( vec.push_back($v); )*
-         vec
-    }}
+fn weight<T, O>(h: &WeightBiasedLeftistHeap<T, O>) -> usize {
+    match *h {
+        WeightBiasedLeftistHeap::Tip(_) => 0,
+        WeightBiasedLeftistHeap::Node(w, _, _, _) => w
+    }
+}
+
+impl<T: Clone, O: Order<T>> Heap<T> for WeightBiasedLeftistHeap<T, O> {
+    fn empty() -> WeightBiasedLeftistHeap<T, O> {
+        WeightBiasedLeftistHeap::Tip(PhantomData)
+    }
+
+    fn is_empty(&self) -> bool {
+        match *self {
+            WeightBiasedLeftistHeap::Tip(_) => true,
+            _ => false
+        }
+    }
+
+    fn merge(&self, h: &WeightBiasedLeftistHeap<T, O>) -> WeightBiasedLeftistHeap<T, O> {
+        // the root of the merged heap keeps one of its children and gets the merge of the other
+        // one with h, whose weight is already known
+        fn make_node<T: Clone, O: Order<T>>(x: &T, a: &Rc<WeightBiasedLeftistHeap<T, O>>, b: &Rc<WeightBiasedLeftistHeap<T, O>>,
+                                            h: &WeightBiasedLeftistHeap<T, O>) -> WeightBiasedLeftistHeap<T, O> {
+            let w = weight(a) + weight(b) + weight(h) + 1;
+
+            if weight(a) >= weight(b) + weight(h) {
+                WeightBiasedLeftistHeap::Node(w, x.clone(), a.clone(), Rc::new(b.merge(h)))
+            } else {
+                WeightBiasedLeftistHeap::Node(w, x.clone(), Rc::new(b.merge(h)), a.clone())
+            }
+        }
+
+        match (self, h) {
+            (e, &WeightBiasedLeftistHeap::Tip(_)) => e.clone(),
+            (&WeightBiasedLeftistHeap::Tip(_), e) => e.clone(),
+            (&WeightBiasedLeftistHeap::Node(_, ref x, ref a1, ref b1), &WeightBiasedLeftistHeap::Node(_, ref y, ref a2, ref b2)) => {
+                if O::leq(x, y) {
+                    make_node(x, a1, b1, h)
+                } else {
+                    make_node(y, a2, b2, self)
+                }
+            }
+        }
+    }
+
+    fn insert(&self, x: T) -> WeightBiasedLeftistHeap<T, O> {
+        let h = WeightBiasedLeftistHeap::Node(1, x, Rc::new(Heap::empty()), Rc::new(Heap::empty()));
+        h.merge(self)
+    }
+
+    fn find_min(&self) -> Option<T> {
+        match *self {
+            WeightBiasedLeftistHeap::Tip(_) => None,
+            WeightBiasedLeftistHeap::Node(_, ref x, _, _) => Some(x.clone())
+        }
+    }
+
+    fn delete_min(&self) -> Option<WeightBiasedLeftistHeap<T, O>> {
+        match *self {
+            WeightBiasedLeftistHeap::Tip(_) => None,
+            WeightBiasedLeftistHeap::Node(_, _, ref l, ref r) => Some(l.merge(r))
+        }
+    }
 }
 
-pub type BinomialHeap<T> = VecDeque<Rc<BinomialTree<T>>>;
+heap_iterators!(WeightBiasedLeftistHeap<T, O> where T: Clone, O: Order<T>);
+
+impl<T: Display, O> Display for WeightBiasedLeftistHeap<T, O> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        fn aux<T: Display, O>(f: &mut Formatter, t: &WeightBiasedLeftistHeap<T, O>, right: bool, indent: &str) -> Result<(), Error> {
+            match *t {
+                WeightBiasedLeftistHeap::Node(ref weight, ref x, ref l, ref r) => {
+                    try!(aux(f, r, true, &(indent.to_string() + if right { "        " } else { " |      " })));
+
+                    try!(write!(f, "{}", indent));
+                    try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
+                    try!(write!(f, "{}", "----- "));
+
+                    try!(writeln!(f, "(#{}, {})", weight, x));
+
+                    aux(f, l, false, &(indent.to_string() + if right { " |      " } else { "        " }))
+                },
+                WeightBiasedLeftistHeap::Tip(_) => {
+                    try!(write!(f, "{}", indent));
+                    try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
+                    try!(write!(f, "{}", "----- "));
+
+                    writeln!(f, "{}", "()")
+                }
+            }
+        }
+
+        match *self {
+            WeightBiasedLeftistHeap::Node(ref weight, ref x, ref l, ref r) => {
+                try!(aux(f, r, true, ""));
+                try!(writeln!(f, "(#{}, {})", weight, x));
+                aux(f, l, false, "")
+            },
+            WeightBiasedLeftistHeap::Tip(_) => Result::Ok(())
+        }
+    }
+}
 
+// Section 3.2:
+// O(log n) worst-case time for every operation, O(1) amortized time for insert
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct BinomialTree<T>(usize, T, BinomialHeap<T>);
+pub struct BinomialTree<T>(usize, T, List<BinomialTree<T>>);
+
+// a list of trees in increasing order of rank, with at most one tree of each rank
+#[derive(Debug, Eq, PartialEq)]
+pub struct BinomialHeap<T, O = Natural>(List<BinomialTree<T>>, PhantomData<O>);
+
+impl<T, O> Clone for BinomialHeap<T, O> {
+    fn clone(&self) -> BinomialHeap<T, O> {
+        BinomialHeap(self.0.clone(), PhantomData)
+    }
+}
 
-fn link<T: Clone + Ord>(t1: &BinomialTree<T>, t2: &BinomialTree<T>) -> BinomialTree<T> {
+// the trees themselves do not know how they are ordered, so the helpers below take the ordering
+// as a type parameter
+fn link<T: Clone, O: Order<T>>(t1: &BinomialTree<T>, t2: &BinomialTree<T>) -> BinomialTree<T> {
     let BinomialTree(r, ref x1, ref c1) = *t1;
     let BinomialTree(_, ref x2, ref c2) = *t2;
 
-    if x1 <= x2 {
-        let mut c = c1.clone();
-        c.push_front(Rc::new(t2.clone()));
-        BinomialTree(r + 1, x1.clone(), c)
+    if O::leq(x1, x2) {
+        BinomialTree(r + 1, x1.clone(), c1.cons(t2.clone()))
     } else {
-        let mut c = c2.clone();
-        c.push_front(Rc::new(t1.clone()));
-        BinomialTree(r + 1, x2.clone(), c)
+        BinomialTree(r + 1, x2.clone(), c2.cons(t1.clone()))
     }
 }
 
@@ -148,118 +381,1046 @@ fn rank<T>(t: &BinomialTree<T>) -> usize {
     r
 }
 
-fn root<T: Clone>(t: &BinomialTree<T>) -> T {
+fn root<T>(t: &BinomialTree<T>) -> &T {
     let BinomialTree(_, ref x, _) = *t;
-    x.clone()
+    x
 }
 
-fn insert_tree<T: Clone + Ord>(h: &BinomialHeap<T>, t: &BinomialTree<T>) -> BinomialHeap<T> {
-    match h.front() {
-        Some(t2) => {
-            let mut h2 = h.clone();
+fn insert_tree<T: Clone, O: Order<T>>(t: BinomialTree<T>, ts: &List<BinomialTree<T>>) -> List<BinomialTree<T>> {
+    match **ts {
+        Cons(ref t2, ref ts2) =>
+            if rank(&t) < rank(t2) {
+                ts.cons(t)
+            } else {
+                insert_tree::<T, O>(link::<T, O>(&t, t2), ts2)
+            },
+        Nil => ts.cons(t)
+    }
+}
 
-            if rank(t) < rank(t2) {
-                h2.push_front(Rc::new(t.clone()));
-                h2
+fn merge_trees<T: Clone, O: Order<T>>(ts1: &List<BinomialTree<T>>, ts2: &List<BinomialTree<T>>) -> List<BinomialTree<T>> {
+    match (&**ts1, &**ts2) {
+        (_, &Nil) => ts1.clone(),
+        (&Nil, _) => ts2.clone(),
+        (&Cons(ref t1, ref ts1_), &Cons(ref t2, ref ts2_)) =>
+            if rank(t1) < rank(t2) {
+                merge_trees::<T, O>(ts1_, ts2).cons(t1.clone())
+            } else if rank(t2) < rank(t1) {
+                merge_trees::<T, O>(ts1, ts2_).cons(t2.clone())
             } else {
-                insert_tree(&h2.split_off(1), &link(t, t2))
+                insert_tree::<T, O>(link::<T, O>(t1, t2), &merge_trees::<T, O>(ts1_, ts2_))
             }
-        },
-        _ => vecdeque![Rc::new(t.clone())],
     }
 }
 
-fn remove_min_tree<T: Clone + Ord>(h: &BinomialHeap<T>) -> (BinomialTree<T>, BinomialHeap<T>) {
-    match h.len() {
-        0 => panic!("remove tree from empty heap"),
-        1 => {
-            ((**h.front().unwrap()).clone(), vecdeque![])
-        },
-        _ => {
-            let t = h.front().unwrap();
-            let ts = h.clone().split_off(1);
+fn remove_min_tree<T: Clone, O: Order<T>>(ts: &List<BinomialTree<T>>) -> Option<(BinomialTree<T>, List<BinomialTree<T>>)> {
+    match **ts {
+        Cons(ref t, ref ts1) =>
+            match remove_min_tree::<T, O>(ts1) {
+                Some((t1, ts2)) =>
+                    if O::leq(root(t), root(&t1)) {
+                        Some((t.clone(), ts1.clone()))
+                    } else {
+                        Some((t1, ts2.cons(t.clone())))
+                    },
+                None => Some((t.clone(), ts1.clone()))
+            },
+        Nil => None
+    }
+}
 
-            let (t1, mut ts1) = remove_min_tree(&ts);
+impl<T: Clone, O: Order<T>> Heap<T> for BinomialHeap<T, O> {
+    fn empty() -> BinomialHeap<T, O> {
+        BinomialHeap(Stack::empty(), PhantomData)
+    }
 
-            if (root(t) < root(&t1)) {
-                ((**t).clone(), ts)
-            } else {
-                ts1.push_front(t.clone());
-                (t1, ts1)
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn merge(&self, h: &BinomialHeap<T, O>) -> BinomialHeap<T, O> {
+        BinomialHeap(merge_trees::<T, O>(&self.0, &h.0), PhantomData)
+    }
+
+    fn insert(&self, x: T) -> BinomialHeap<T, O> {
+        BinomialHeap(insert_tree::<T, O>(BinomialTree(0, x, Stack::empty()), &self.0), PhantomData)
+    }
+
+    // Exercise 3.5:
+    // scans the roots directly instead of going through remove_min_tree
+    fn find_min(&self) -> Option<T> {
+        self.0.iter().map(root).min_by(|x, y| O::compare(x, y)).cloned()
+    }
+
+    fn delete_min(&self) -> Option<BinomialHeap<T, O>> {
+        remove_min_tree::<T, O>(&self.0).map(|(BinomialTree(_, _, ts1), ts2)| {
+            BinomialHeap(merge_trees::<T, O>(&ts1.reverse(), &ts2), PhantomData)
+        })
+    }
+
+    // inserting into a heap built from scratch is like incrementing a binary counter, which takes
+    // O(1) amortized time
+    fn build<I: IntoIterator<Item = T>>(xs: I) -> BinomialHeap<T, O> {
+        xs.into_iter().fold(Heap::empty(), |h: BinomialHeap<T, O>, x| h.insert(x))
+    }
+}
+
+heap_iterators!(BinomialHeap<T, O> where T: Clone, O: Order<T>);
+
+impl<T, O: Order<T>> BinomialHeap<T, O> {
+    /// Checks that the trees are in strictly increasing order of rank, that every tree of rank r
+    /// has exactly r children of ranks r - 1 down to 0, and that every tree is heap ordered.
+    pub fn check_invariants(&self) -> bool {
+        fn valid<T, O: Order<T>>(t: &BinomialTree<T>) -> bool {
+            let BinomialTree(r, ref x, ref c) = *t;
+
+            c.iter().count() == r &&
+                c.iter().enumerate().all(|(i, c)| rank(c) + i + 1 == r && O::leq(x, root(c)) && valid::<T, O>(c))
+        }
+
+        let ranks: Vec<usize> = self.0.iter().map(rank).collect();
+
+        ranks.windows(2).all(|w| w[0] < w[1]) && self.0.iter().all(valid::<T, O>)
+    }
+}
+
+// drawn as its binary tree representation, where the left child of a node is its first child and
+// the right child is its next sibling, the roots of the heap being siblings of each other
+impl<T: Display, O> Display for BinomialHeap<T, O> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        fn first<T>(ts: &List<BinomialTree<T>>) -> Option<(&BinomialTree<T>, &List<BinomialTree<T>>)> {
+            match **ts {
+                Cons(ref t, ref siblings) => Some((t, siblings)),
+                Nil => None
+            }
+        }
+
+        fn aux<T: Display>(f: &mut Formatter, t: Option<(&BinomialTree<T>, &List<BinomialTree<T>>)>,
+                           right: bool, indent: &str) -> Result<(), Error> {
+            match t {
+                Some((&BinomialTree(ref rank, ref x, ref children), siblings)) => {
+                    try!(aux(f, first(siblings), true, &(indent.to_string() + if right { "        " } else { " |      " })));
+
+                    try!(write!(f, "{}", indent));
+                    try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
+                    try!(write!(f, "{}", "----- "));
+
+                    try!(writeln!(f, "(#{}, {})", rank, x));
+
+                    aux(f, first(children), false, &(indent.to_string() + if right { " |      " } else { "        " }))
+                },
+                None => {
+                    try!(write!(f, "{}", indent));
+                    try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
+                    try!(write!(f, "{}", "----- "));
+
+                    writeln!(f, "{}", "()")
+                }
             }
         }
+
+        match first(&self.0) {
+            Some((&BinomialTree(ref rank, ref x, ref children), siblings)) => {
+                try!(aux(f, first(siblings), true, ""));
+                try!(writeln!(f, "(#{}, {})", rank, x));
+                aux(f, first(children), false, "")
+            },
+            None => Result::Ok(())
+        }
+    }
+}
+
+// Section 9.3.2:
+// O(1) worst-case time for insert, O(log n) worst-case time for every other operation. Every
+// node keeps, besides its root, the elements that were skew-linked into it, so that the trees
+// are still binomial trees and can reuse link, rank and the helpers built on top of them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct SkewNode<T>(T, List<T>);
+
+// orders the nodes by their root alone, which is all that link and remove_min_tree look at
+struct SkewRoot<O>(PhantomData<O>);
+
+impl<T, O: Order<T>> Order<SkewNode<T>> for SkewRoot<O> {
+    fn compare(x: &SkewNode<T>, y: &SkewNode<T>) -> Ordering {
+        O::compare(&x.0, &y.0)
+    }
+}
+
+impl<T: Display> Display for SkewNode<T> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self.1 {
+            Nil => write!(f, "{}", self.0),
+            Cons(..) => write!(f, "{} {}", self.0, self.1)
+        }
+    }
+}
+
+// a list of trees in increasing order of rank, except that the first two trees may share the
+// smallest rank
+#[derive(Debug, Eq, PartialEq)]
+pub struct SkewBinomialHeap<T, O = Natural>(List<BinomialTree<SkewNode<T>>>, PhantomData<O>);
+
+impl<T, O> Clone for SkewBinomialHeap<T, O> {
+    fn clone(&self) -> SkewBinomialHeap<T, O> {
+        SkewBinomialHeap(self.0.clone(), PhantomData)
+    }
+}
+
+fn skew_link<T: Clone, O: Order<T>>(x: T, t1: &BinomialTree<SkewNode<T>>, t2: &BinomialTree<SkewNode<T>>) -> BinomialTree<SkewNode<T>> {
+    let BinomialTree(r, SkewNode(y, ys), c) = link::<_, SkewRoot<O>>(t1, t2);
+
+    if O::leq(&x, &y) {
+        BinomialTree(r, SkewNode(x, ys.cons(y)), c)
+    } else {
+        BinomialTree(r, SkewNode(y, ys.cons(x)), c)
+    }
+}
+
+// gets rid of the duplicate rank at the front, so that the trees can go through merge_trees
+fn normalize<T: Clone, O: Order<T>>(ts: &List<BinomialTree<T>>) -> List<BinomialTree<T>> {
+    match **ts {
+        Cons(ref t, ref ts1) => insert_tree::<T, O>(t.clone(), ts1),
+        Nil => ts.clone()
     }
 }
 
-impl<T: Ord + Clone> Heap<T> for BinomialHeap<T> {
-    fn empty() -> BinomialHeap<T> {
-        vecdeque![]
+impl<T: Clone, O: Order<T>> Heap<T> for SkewBinomialHeap<T, O> {
+    fn empty() -> SkewBinomialHeap<T, O> {
+        SkewBinomialHeap(Stack::empty(), PhantomData)
     }
 
     fn is_empty(&self) -> bool {
-        self.is_empty()
+        self.0.is_empty()
+    }
+
+    fn merge(&self, h: &SkewBinomialHeap<T, O>) -> SkewBinomialHeap<T, O> {
+        let ts = merge_trees::<_, SkewRoot<O>>(&normalize::<_, SkewRoot<O>>(&self.0), &normalize::<_, SkewRoot<O>>(&h.0));
+        SkewBinomialHeap(ts, PhantomData)
+    }
+
+    // links at most once, with the two smallest trees when they have the same rank
+    fn insert(&self, x: T) -> SkewBinomialHeap<T, O> {
+        if let Cons(ref t1, ref ts) = *self.0 {
+            if let Cons(ref t2, ref rest) = **ts {
+                if rank(t1) == rank(t2) {
+                    return SkewBinomialHeap(rest.cons(skew_link::<T, O>(x, t1, t2)), PhantomData);
+                }
+            }
+        }
+
+        SkewBinomialHeap(self.0.cons(BinomialTree(0, SkewNode(x, Stack::empty()), Stack::empty())), PhantomData)
+    }
+
+    fn find_min(&self) -> Option<T> {
+        self.0.iter().map(|t| &root(t).0).min_by(|x, y| O::compare(x, y)).cloned()
+    }
+
+    // the children go back in like in a binomial heap, and the skew elements are reinserted one
+    // at a time
+    fn delete_min(&self) -> Option<SkewBinomialHeap<T, O>> {
+        remove_min_tree::<_, SkewRoot<O>>(&self.0).map(|(BinomialTree(_, SkewNode(_, xs), ts1), ts2)| {
+            let h = SkewBinomialHeap(ts1.reverse(), PhantomData).merge(&SkewBinomialHeap(ts2, PhantomData));
+            xs.iter().fold(h, |h, x| h.insert(x.clone()))
+        })
+    }
+
+    fn build<I: IntoIterator<Item = T>>(xs: I) -> SkewBinomialHeap<T, O> {
+        xs.into_iter().fold(Heap::empty(), |h: SkewBinomialHeap<T, O>, x| h.insert(x))
+    }
+}
+
+heap_iterators!(SkewBinomialHeap<T, O> where T: Clone, O: Order<T>);
+
+// drawn like a binomial heap, with the skew elements of every node listed after its root
+impl<T: Display, O> Display for SkewBinomialHeap<T, O> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}", BinomialHeap::<SkewNode<T>, O>(self.0.clone(), PhantomData))
+    }
+}
+
+// Section 5.5:
+// O(1) merge, insert and find_min, O(log n) amortized delete_min
+#[derive(Debug, Eq, PartialEq)]
+pub enum PairingHeap<T, O = Natural> {
+    Tip(PhantomData<O>),
+    Node(T, List<PairingHeap<T, O>>),
+}
+
+impl<T: Clone, O> Clone for PairingHeap<T, O> {
+    fn clone(&self) -> PairingHeap<T, O> {
+        match *self {
+            PairingHeap::Tip(_) => PairingHeap::Tip(PhantomData),
+            PairingHeap::Node(ref x, ref hs) => PairingHeap::Node(x.clone(), hs.clone())
+        }
+    }
+}
+
+// merges the subtrees of a deleted root in two passes: first in pairs from left to right, then the
+// resulting heaps from right to left
+fn merge_pairs<T: Clone, O: Order<T>>(hs: &List<PairingHeap<T, O>>) -> PairingHeap<T, O> {
+    let mut pairs = vec![];
+    let mut hs = hs.iter();
+
+    while let Some(h1) = hs.next() {
+        match hs.next() {
+            Some(h2) => pairs.push(h1.merge(h2)),
+            None => pairs.push(h1.clone())
+        }
     }
 
-    fn merge(&self, h: &BinomialHeap<T>) -> BinomialHeap<T> {
-        match (self.front(), h.front()) {
-            (_, None) => self.clone(),
-            (None, _) => h.clone(),
-            (Some(t1), Some(t2)) => {
-                if rank(t1) < rank(t2) {
+    pairs.iter().rev().fold(Heap::empty(), |h, p| p.merge(&h))
+}
+
+impl<T: Clone, O: Order<T>> Heap<T> for PairingHeap<T, O> {
+    fn empty() -> PairingHeap<T, O> {
+        PairingHeap::Tip(PhantomData)
+    }
 
-                    let mut h = self.clone().split_off(1).merge(h);
-                    h.push_front(t1.clone());
-                    h
-                } else if rank(t2) < rank(t1) {
+    fn is_empty(&self) -> bool {
+        match *self {
+            PairingHeap::Tip(_) => true,
+            _ => false
+        }
+    }
 
-                    let mut h = self.merge(&h.clone().split_off(1));
-                    h.push_front(t2.clone());
-                    h
+    fn merge(&self, h: &PairingHeap<T, O>) -> PairingHeap<T, O> {
+        match (self, h) {
+            (e, &PairingHeap::Tip(_)) => e.clone(),
+            (&PairingHeap::Tip(_), e) => e.clone(),
+            (&PairingHeap::Node(ref x, ref hs1), &PairingHeap::Node(ref y, ref hs2)) => {
+                if O::leq(x, y) {
+                    PairingHeap::Node(x.clone(), hs1.cons(h.clone()))
                 } else {
-                    insert_tree(
-                        &self.clone().split_off(1).merge(&h.clone().split_off(2)),
-                        &link(t1, t2))
+                    PairingHeap::Node(y.clone(), hs2.cons(self.clone()))
                 }
             }
         }
     }
 
-    fn insert(&self, x: T) -> BinomialHeap<T> {
-        insert_tree(self, &BinomialTree(0, x, vecdeque![]))
+    fn insert(&self, x: T) -> PairingHeap<T, O> {
+        PairingHeap::Node(x, Stack::empty()).merge(self)
+    }
+
+    fn find_min(&self) -> Option<T> {
+        match *self {
+            PairingHeap::Tip(_) => None,
+            PairingHeap::Node(ref x, _) => Some(x.clone())
+        }
     }
 
-    fn find_min(&self) -> T {
-        let (t, _) = remove_min_tree(self);
-        root(&t)
+    fn delete_min(&self) -> Option<PairingHeap<T, O>> {
+        match *self {
+            PairingHeap::Tip(_) => None,
+            PairingHeap::Node(_, ref hs) => Some(merge_pairs(hs))
+        }
     }
 
-    fn delete_min(&self) -> BinomialHeap<T> {
-        let (BinomialTree(_, _, ts1), ts2) = remove_min_tree(self);
-        let ts1: BinomialHeap<T> = ts1.into_iter().rev().collect();
-        ts1.merge(&ts2)
+    fn build<I: IntoIterator<Item = T>>(xs: I) -> PairingHeap<T, O> {
+        xs.into_iter().fold(Heap::empty(), |h: PairingHeap<T, O>, x| h.insert(x))
     }
 }
 
-#[test]
-fn leftistheap() {
-    let h: LeftistHeap<usize> = Heap::empty();
-    let h2: LeftistHeap<usize> = h.insert(10).insert(9).insert(8).insert(11).insert(1).insert(4);
+heap_iterators!(PairingHeap<T, O> where T: Clone, O: Order<T>);
 
-    assert!(h.is_empty());
-    assert!(!h2.is_empty());
+// drawn as its binary tree representation, where the left child of a node is its first subtree
+// and the right child is its next sibling
+impl<T: Display, O> Display for PairingHeap<T, O> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        // a node's element, its first subtree and its next sibling
+        type Binary<'a, T, O> = Option<(&'a T, &'a List<PairingHeap<T, O>>, &'a List<PairingHeap<T, O>>)>;
 
-    assert_eq!(h2.find_min(), 1);
-    assert_eq!(h2.delete_min(), h.insert(10).insert(9).insert(8).insert(11).insert(4));
-}
+        fn first<'a, T, O>(hs: &'a List<PairingHeap<T, O>>) -> Binary<'a, T, O> {
+            match **hs {
+                Cons(PairingHeap::Node(ref x, ref children), ref siblings) => Some((x, children, siblings)),
+                _ => None
+            }
+        }
 
-#[test]
-fn binomialheap() {
-    let h: BinomialHeap<usize> = Heap::empty();
-    let h2: BinomialHeap<usize> = h.insert(10).insert(9).insert(8).insert(11).insert(1).insert(4);
+        fn aux<T: Display, O>(f: &mut Formatter, t: Binary<T, O>, right: bool, indent: &str) -> Result<(), Error> {
+            match t {
+                Some((x, children, siblings)) => {
+                    try!(aux(f, first(siblings), true, &(indent.to_string() + if right { "        " } else { " |      " })));
 
-    assert!(h.is_empty());
-    assert!(!h2.is_empty());
+                    try!(write!(f, "{}", indent));
+                    try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
+                    try!(write!(f, "{}", "----- "));
+
+                    try!(writeln!(f, "({})", x));
+
+                    aux(f, first(children), false, &(indent.to_string() + if right { " |      " } else { "        " }))
+                },
+                None => {
+                    try!(write!(f, "{}", indent));
+                    try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
+                    try!(write!(f, "{}", "----- "));
+
+                    writeln!(f, "{}", "()")
+                }
+            }
+        }
+
+        match *self {
+            PairingHeap::Node(ref x, ref children) => {
+                try!(aux::<T, O>(f, None, true, ""));
+                try!(writeln!(f, "({})", x));
+                aux(f, first(children), false, "")
+            },
+            PairingHeap::Tip(_) => Result::Ok(())
+        }
+    }
+}
+
+// Section 5.4:
+// O(log n) amortized time for every operation. find_min walks down the left spine, which takes
+// O(n) worst-case, O(log n) amortized time. As with the pairing heap, whose delete_min is
+// amortized too, these bounds only hold when the heap is used ephemerally: an expensive operation
+// can be repeated on the same old version again and again.
+#[derive(Debug, Eq, PartialEq)]
+pub enum SplayHeap<T, O = Natural> {
+    Tip(PhantomData<O>),
+    Node(Rc<SplayHeap<T, O>>, T, Rc<SplayHeap<T, O>>),
+}
+
+impl<T: Clone, O> Clone for SplayHeap<T, O> {
+    fn clone(&self) -> SplayHeap<T, O> {
+        match *self {
+            SplayHeap::Tip(_) => SplayHeap::Tip(PhantomData),
+            SplayHeap::Node(ref a, ref x, ref b) => SplayHeap::Node(a.clone(), x.clone(), b.clone())
+        }
+    }
+}
+
+type Split<T, O> = (Rc<SplayHeap<T, O>>, Rc<SplayHeap<T, O>>);
+
+// splits the heap into the elements smaller or equal than the pivot and those bigger than it,
+// rotating along the way whenever it follows two left or two right branches in a row
+fn partition<T: Clone, O: Order<T>>(pivot: &T, t: &Rc<SplayHeap<T, O>>) -> Split<T, O> {
+    match **t {
+        SplayHeap::Tip(_) => (t.clone(), t.clone()),
+        SplayHeap::Node(ref a, ref x, ref b) =>
+            if O::leq(x, pivot) {
+                match **b {
+                    SplayHeap::Tip(_) => (t.clone(), b.clone()),
+                    SplayHeap::Node(ref b1, ref y, ref b2) =>
+                        if O::leq(y, pivot) {
+                            let (small, big) = partition(pivot, b2);
+                            let l = Rc::new(SplayHeap::Node(a.clone(), x.clone(), b1.clone()));
+                            (Rc::new(SplayHeap::Node(l, y.clone(), small)), big)
+                        } else {
+                            let (small, big) = partition(pivot, b1);
+                            (Rc::new(SplayHeap::Node(a.clone(), x.clone(), small)),
+                             Rc::new(SplayHeap::Node(big, y.clone(), b2.clone())))
+                        }
+                }
+            } else {
+                match **a {
+                    SplayHeap::Tip(_) => (a.clone(), t.clone()),
+                    SplayHeap::Node(ref a1, ref y, ref a2) =>
+                        if O::leq(y, pivot) {
+                            let (small, big) = partition(pivot, a2);
+                            (Rc::new(SplayHeap::Node(a1.clone(), y.clone(), small)),
+                             Rc::new(SplayHeap::Node(big, x.clone(), b.clone())))
+                        } else {
+                            let (small, big) = partition(pivot, a1);
+                            let r = Rc::new(SplayHeap::Node(a2.clone(), x.clone(), b.clone()));
+                            (small, Rc::new(SplayHeap::Node(big, y.clone(), r)))
+                        }
+                }
+            }
+    }
+}
+
+impl<T: Clone, O: Order<T>> Heap<T> for SplayHeap<T, O> {
+    fn empty() -> SplayHeap<T, O> {
+        SplayHeap::Tip(PhantomData)
+    }
+
+    fn is_empty(&self) -> bool {
+        match *self {
+            SplayHeap::Tip(_) => true,
+            _ => false
+        }
+    }
+
+    fn merge(&self, h: &SplayHeap<T, O>) -> SplayHeap<T, O> {
+        match *self {
+            SplayHeap::Tip(_) => h.clone(),
+            SplayHeap::Node(ref a, ref x, ref b) => {
+                let (ta, tb) = partition(x, &Rc::new(h.clone()));
+                SplayHeap::Node(Rc::new(ta.merge(a)), x.clone(), Rc::new(tb.merge(b)))
+            }
+        }
+    }
+
+    fn insert(&self, x: T) -> SplayHeap<T, O> {
+        let (a, b) = partition(&x, &Rc::new(self.clone()));
+        SplayHeap::Node(a, x, b)
+    }
+
+    fn find_min(&self) -> Option<T> {
+        match *self {
+            SplayHeap::Tip(_) => None,
+            SplayHeap::Node(ref a, ref x, _) => a.find_min().or_else(|| Some(x.clone()))
+        }
+    }
+
+    fn delete_min(&self) -> Option<SplayHeap<T, O>> {
+        match *self {
+            SplayHeap::Tip(_) => None,
+            SplayHeap::Node(ref a, ref y, ref c) =>
+                match **a {
+                    SplayHeap::Tip(_) => Some((**c).clone()),
+                    SplayHeap::Node(ref a1, ref x, ref b) =>
+                        match **a1 {
+                            SplayHeap::Tip(_) =>
+                                Some(SplayHeap::Node(b.clone(), y.clone(), c.clone())),
+                            _ =>
+                                a1.delete_min().map(|a2| {
+                                    let r = Rc::new(SplayHeap::Node(b.clone(), y.clone(), c.clone()));
+                                    SplayHeap::Node(Rc::new(a2), x.clone(), r)
+                                })
+                        }
+                }
+        }
+    }
+}
+
+heap_iterators!(SplayHeap<T, O> where T: Clone, O: Order<T>);
+
+impl<T: Display, O> Display for SplayHeap<T, O> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        fn aux<T: Display, O>(f: &mut Formatter, t: &SplayHeap<T, O>, right: bool, indent: &str) -> Result<(), Error> {
+            match *t {
+                SplayHeap::Node(ref l, ref x, ref r) => {
+                    try!(aux(f, r, true, &(indent.to_string() + if right { "        " } else { " |      " })));
+
+                    try!(write!(f, "{}", indent));
+                    try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
+                    try!(write!(f, "{}", "----- "));
+
+                    try!(writeln!(f, "({})", x));
+
+                    aux(f, l, false, &(indent.to_string() + if right { " |      " } else { "        " }))
+                },
+                SplayHeap::Tip(_) => {
+                    try!(write!(f, "{}", indent));
+                    try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
+                    try!(write!(f, "{}", "----- "));
+
+                    writeln!(f, "{}", "()")
+                }
+            }
+        }
+
+        match *self {
+            SplayHeap::Node(ref l, ref x, ref r) => {
+                try!(aux(f, r, true, ""));
+                try!(writeln!(f, "({})", x));
+                aux(f, l, false, "")
+            },
+            SplayHeap::Tip(_) => Result::Ok(())
+        }
+    }
+}
+
+// Exercise 3.7:
+// O(1) worst-case time for find_min on top of any heap, by keeping its minimum next to it. The
+// other operations take as long as they take on the wrapped heap, plus one of its find_min to read
+// the new minimum back, so that it always agrees with whatever ordering the wrapped heap uses.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExplicitMin<T, H>(Option<(T, H)>);
+
+fn explicit_min<T, H: Heap<T>>(h: H) -> ExplicitMin<T, H> {
+    ExplicitMin(h.find_min().map(|x| (x, h)))
+}
+
+impl<T: Clone, H: Heap<T> + Clone> Heap<T> for ExplicitMin<T, H> {
+    fn empty() -> ExplicitMin<T, H> {
+        ExplicitMin(None)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_none()
+    }
+
+    fn merge(&self, h: &ExplicitMin<T, H>) -> ExplicitMin<T, H> {
+        match (&self.0, &h.0) {
+            (&Some((_, ref h1)), &Some((_, ref h2))) => explicit_min(h1.merge(h2)),
+            (&None, _) => h.clone(),
+            (_, &None) => self.clone()
+        }
+    }
+
+    fn insert(&self, x: T) -> ExplicitMin<T, H> {
+        match self.0 {
+            Some((_, ref h)) => explicit_min(h.insert(x)),
+            None => explicit_min(H::empty().insert(x))
+        }
+    }
+
+    fn find_min(&self) -> Option<T> {
+        self.0.as_ref().map(|&(ref x, _)| x.clone())
+    }
+
+    fn delete_min(&self) -> Option<ExplicitMin<T, H>> {
+        self.0.as_ref().map(|&(_, ref h)| {
+            match h.delete_min() {
+                Some(h1) => explicit_min(h1),
+                None => ExplicitMin(None)
+            }
+        })
+    }
+
+    fn build<I: IntoIterator<Item = T>>(xs: I) -> ExplicitMin<T, H> {
+        explicit_min(H::build(xs))
+    }
+}
+
+heap_iterators!(ExplicitMin<T, H> where T: Clone, H: Heap<T> + Clone);
+
+impl<T, H: Display> Display for ExplicitMin<T, H> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self.0 {
+            Some((_, ref h)) => write!(f, "{}", h),
+            None => Result::Ok(())
+        }
+    }
+}
+
+/// A heap type constructor, standing for a whole family of heaps of any element type, so that a
+/// heap can be parameterized by the kind of heap it is built from.
+pub trait HeapFamily {
+    type Of<T: Ord + Clone>: Heap<T> + Clone;
+}
+
+macro_rules! heap_family {
+    ($family: ident, $heap: ident) => {
+        #[derive(Clone, Copy, Debug)]
+        pub struct $family;
+
+        impl HeapFamily for $family {
+            type Of<T: Ord + Clone> = $heap<T>;
+        }
+    }
+}
+
+heap_family!(LeftistHeapFamily, LeftistHeap);
+heap_family!(WeightBiasedLeftistHeapFamily, WeightBiasedLeftistHeap);
+heap_family!(BinomialHeapFamily, BinomialHeap);
+heap_family!(SkewBinomialHeapFamily, SkewBinomialHeap);
+heap_family!(PairingHeapFamily, PairingHeap);
+heap_family!(SplayHeapFamily, SplayHeap);
+
+// Section 10.2.2:
+// O(1) worst-case time for find_min, and for merge and insert as long as the primitive heap
+// inserts in O(1) worst-case time, like the skew binomial heap does. delete_min takes as long as
+// the delete_min of the primitive heap.
+pub struct BootstrappedNode<T: Clone, F: HeapFamily, O: Order<T> = Natural>(T, Rc<F::Of<BootstrappedNode<T, F, O>>>);
+
+// a non-empty heap whose minimum is kept at the root, and whose other elements are stored in
+// the primitive heap as bootstrapped heaps themselves
+pub struct BootstrappedHeap<T: Clone, F: HeapFamily, O: Order<T> = Natural>(Option<BootstrappedNode<T, F, O>>);
+
+impl<T: Clone, F: HeapFamily, O: Order<T>> Clone for BootstrappedNode<T, F, O> {
+    fn clone(&self) -> BootstrappedNode<T, F, O> {
+        BootstrappedNode(self.0.clone(), self.1.clone())
+    }
+}
+
+impl<T: Clone, F: HeapFamily, O: Order<T>> Clone for BootstrappedHeap<T, F, O> {
+    fn clone(&self) -> BootstrappedHeap<T, F, O> {
+        BootstrappedHeap(self.0.clone())
+    }
+}
+
+// nodes are ordered by their root alone, which is all that the primitive heap looks at
+impl<T: Clone, F: HeapFamily, O: Order<T>> Ord for BootstrappedNode<T, F, O> {
+    fn cmp(&self, other: &BootstrappedNode<T, F, O>) -> Ordering {
+        O::compare(&self.0, &other.0)
+    }
+}
+
+impl<T: Clone, F: HeapFamily, O: Order<T>> PartialOrd for BootstrappedNode<T, F, O> {
+    fn partial_cmp(&self, other: &BootstrappedNode<T, F, O>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Clone, F: HeapFamily, O: Order<T>> PartialEq for BootstrappedNode<T, F, O> {
+    fn eq(&self, other: &BootstrappedNode<T, F, O>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Clone, F: HeapFamily, O: Order<T>> Eq for BootstrappedNode<T, F, O> {}
+
+fn merge_nodes<T: Clone, F: HeapFamily, O: Order<T>>(h1: &BootstrappedNode<T, F, O>, h2: &BootstrappedNode<T, F, O>) -> BootstrappedNode<T, F, O> {
+    if O::leq(&h1.0, &h2.0) {
+        BootstrappedNode(h1.0.clone(), Rc::new(h1.1.insert(h2.clone())))
+    } else {
+        BootstrappedNode(h2.0.clone(), Rc::new(h2.1.insert(h1.clone())))
+    }
+}
+
+impl<T: Clone, F: HeapFamily, O: Order<T>> Heap<T> for BootstrappedHeap<T, F, O> {
+    fn empty() -> BootstrappedHeap<T, F, O> {
+        BootstrappedHeap(None)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_none()
+    }
+
+    fn merge(&self, h: &BootstrappedHeap<T, F, O>) -> BootstrappedHeap<T, F, O> {
+        match (&self.0, &h.0) {
+            (&Some(ref h1), &Some(ref h2)) => BootstrappedHeap(Some(merge_nodes(h1, h2))),
+            (&None, _) => h.clone(),
+            (_, &None) => self.clone()
+        }
+    }
+
+    fn insert(&self, x: T) -> BootstrappedHeap<T, F, O> {
+        BootstrappedHeap(Some(BootstrappedNode(x, Rc::new(Heap::empty())))).merge(self)
+    }
+
+    fn find_min(&self) -> Option<T> {
+        self.0.as_ref().map(|h| h.0.clone())
+    }
+
+    // the new root is the smallest of the heaps in the primitive heap, which takes over the rest
+    // of them
+    fn delete_min(&self) -> Option<BootstrappedHeap<T, F, O>> {
+        self.0.as_ref().map(|h| {
+            match h.1.pop_min() {
+                Some((BootstrappedNode(y, p1), p2)) => BootstrappedHeap(Some(BootstrappedNode(y, Rc::new(p1.merge(&p2))))),
+                None => BootstrappedHeap(None)
+            }
+        })
+    }
+
+    fn build<I: IntoIterator<Item = T>>(xs: I) -> BootstrappedHeap<T, F, O> {
+        xs.into_iter().fold(Heap::empty(), |h: BootstrappedHeap<T, F, O>, x| h.insert(x))
+    }
+}
+
+heap_iterators!(BootstrappedHeap<T, F, O> where T: Clone, F: HeapFamily, O: Order<T>);
+
+#[cfg(test)]
+fn min_heap<H: Heap<usize> + Clone>() {
+    let h: H = Heap::empty();
+    let h2 = h.insert(10).insert(9).insert(8).insert(11).insert(1).insert(4);
+
+    assert!(h.is_empty());
+    assert!(!h2.is_empty());
+
+    assert_eq!(h2.find_min(), Some(1));
+    assert_eq!(h2.delete_min().map(|h| h.iter().collect::<Vec<usize>>()), Some(vec![4, 8, 9, 10, 11]));
+    assert_eq!(h2.merge(&h.insert(5).insert(0)).iter().collect::<Vec<usize>>(), vec![0, 1, 4, 5, 8, 9, 10, 11]);
+
+    assert_eq!(h.find_min(), None);
+    assert!(h.delete_min().is_none());
+    assert!(h.pop_min().is_none());
+}
+
+// random inserts, merges and deletions on a few heaps, each checked against a vector of its
+// elements and by `valid` after every operation
+#[cfg(test)]
+fn against_vectors<H: Heap<usize> + Clone, F: Fn(&H) -> bool>(valid: F) {
+    let h: H = Heap::empty();
+    let mut heaps = [h.clone(), h.clone(), h.clone()];
+    let mut contents: [Vec<usize>; 3] = [vec![], vec![], vec![]];
+
+    for (i, x) in ::random(1009).take(2000).enumerate() {
+        let a = x % 3;
+        let b = (x / 3) % 3;
+
+        match x % 7 {
+            0 if a != b => {
+                heaps[a] = heaps[a].merge(&heaps[b]);
+                heaps[b] = h.clone();
+                let c = contents[b].split_off(0);
+                contents[a].extend(c);
+            },
+            1 | 2 => {
+                if let Some((m, h)) = heaps[a].pop_min() {
+                    let i = contents[a].iter().position(|y| *y == m).unwrap();
+                    assert_eq!(contents[a].iter().min(), Some(&m));
+                    contents[a].remove(i);
+                    heaps[a] = h;
+                }
+            },
+            _ => {
+                heaps[a] = heaps[a].insert(i);
+                contents[a].push(i);
+            }
+        }
+
+        assert!(valid(&heaps[a]));
+        assert_eq!(heaps[a].find_min(), contents[a].iter().min().cloned());
+        assert_eq!(heaps[a].is_empty(), contents[a].is_empty());
+    }
+
+    for (h, c) in heaps.iter().zip(contents.iter_mut()) {
+        c.sort();
+        assert_eq!(h.iter().collect::<Vec<usize>>(), *c);
+    }
+}
+
+#[test]
+fn leftistheap() {
+    let h: LeftistHeap<usize> = Heap::empty();
+    let h2: LeftistHeap<usize> = h.insert(10).insert(9).insert(8).insert(11).insert(1).insert(4);
+
+    assert!(h.is_empty());
+    assert!(!h2.is_empty());
+
+    assert_eq!(h2.find_min(), Some(1));
+    assert_eq!(h2.delete_min(), Some(h.insert(10).insert(9).insert(8).insert(11).insert(4)));
+    assert_eq!(h2.pop_min(), Some((1, h.insert(10).insert(9).insert(8).insert(11).insert(4))));
+
+    assert_eq!(h.find_min(), None);
+    assert_eq!(h.delete_min(), None);
+    assert_eq!(h.pop_min(), None);
+}
+
+#[test]
+fn leftistheap_order() {
+    use order::{ByKey, Projection, Reverse};
+
+    struct Priority;
+
+    impl Projection<(usize, &'static str)> for Priority {
+        type Key = usize;
+
+        fn key(x: &(usize, &'static str)) -> usize {
+            x.0
+        }
+    }
+
+    let h: LeftistHeap<usize, Reverse> = vec![10, 9, 8, 11, 1, 4].into_iter().collect();
+
+    assert_eq!(h.find_min(), Some(11));
+    assert_eq!(h.insert(12).find_min(), Some(12));
+    assert_eq!(h.iter().collect::<Vec<usize>>(), vec![11, 10, 9, 8, 4, 1]);
+    assert_eq!(heap_sort::<LeftistHeap<usize, Reverse>, usize>(vec![2, 3, 1]), vec![3, 2, 1]);
+
+    // the tasks are ordered by their priority alone
+    let tasks: LeftistHeap<(usize, &str), ByKey<Priority>> = vec![(2, "b"), (1, "a"), (3, "c")].into_iter().collect();
+
+    assert_eq!(tasks.find_min(), Some((1, "a")));
+    assert_eq!(tasks.delete_min().and_then(|h| h.find_min()), Some((2, "b")));
+}
+
+#[test]
+fn heap_order() {
+    use order::Reverse;
+
+    fn check<H: Heap<usize> + Clone>() {
+        let h = H::build(vec![10, 9, 8, 11, 1, 4]);
+
+        assert_eq!(h.find_min(), Some(11));
+        assert_eq!(h.insert(12).find_min(), Some(12));
+        assert_eq!(h.merge(&H::empty().insert(5)).into_sorted_vec(), vec![11, 10, 9, 8, 5, 4, 1]);
+    }
+
+    check::<WeightBiasedLeftistHeap<usize, Reverse>>();
+    check::<BinomialHeap<usize, Reverse>>();
+    check::<SkewBinomialHeap<usize, Reverse>>();
+    check::<PairingHeap<usize, Reverse>>();
+    check::<SplayHeap<usize, Reverse>>();
+    check::<ExplicitMin<usize, LeftistHeap<usize, Reverse>>>();
+    check::<BootstrappedHeap<usize, SkewBinomialHeapFamily, Reverse>>();
+
+    assert!((0..100).collect::<BinomialHeap<usize, Reverse>>().check_invariants());
+}
+
+#[test]
+fn weightbiasedleftistheap() {
+    min_heap::<WeightBiasedLeftistHeap<usize>>();
+
+    let h: WeightBiasedLeftistHeap<usize> = Heap::empty();
+    let h2: WeightBiasedLeftistHeap<usize> = h.insert(10).insert(9).insert(8).insert(11).insert(1).insert(4);
+
+    // every left child weighs at least as much as its sibling, and every node weighs its size
+    fn valid(h: &WeightBiasedLeftistHeap<usize>) -> bool {
+        match *h {
+            WeightBiasedLeftistHeap::Node(w, _, ref l, ref r) =>
+                w == weight(l) + weight(r) + 1 && weight(l) >= weight(r) && valid(l) && valid(r),
+            WeightBiasedLeftistHeap::Tip(_) => true
+        }
+    }
+
+    let h3: WeightBiasedLeftistHeap<usize> = (0..100).map(|x| x * 37 % 100).collect();
+
+    assert!(valid(&h3));
+    assert!(valid(&h3.delete_min().unwrap().merge(&h2)));
+    assert_eq!(weight(&h3), 100);
+}
+
+#[test]
+fn explicitmin() {
+    use order::Reverse;
+
+    min_heap::<ExplicitMin<usize, BinomialHeap<usize>>>();
+
+    let h: ExplicitMin<usize, BinomialHeap<usize>> = Heap::empty();
+    let h2 = h.insert(10).insert(9).insert(8).insert(11).insert(1).insert(4);
+
+    assert_eq!(h2.delete_min().and_then(|h| h.find_min()), Some(4));
+    assert_eq!(h.merge(&h2).find_min(), Some(1));
+
+    let l: ExplicitMin<usize, LeftistHeap<usize>> = vec![3, 1, 2].into_iter().collect();
+
+    assert_eq!(l.find_min(), Some(1));
+    assert_eq!(l.into_iter().collect::<Vec<usize>>(), vec![1, 2, 3]);
+
+    // the minimum is whatever the wrapped heap orders first
+    let r: ExplicitMin<usize, LeftistHeap<usize, Reverse>> = vec![1, 3, 2].into_iter().collect();
+
+    assert_eq!(r.find_min(), Some(3));
+    assert_eq!(r.merge(&vec![0, 4].into_iter().collect()).iter().collect::<Vec<usize>>(), vec![4, 3, 2, 1, 0]);
+    assert_eq!(r.into_iter().collect::<Vec<usize>>(), vec![3, 2, 1]);
+    assert_eq!(h.insert(1).delete_min(), Some(h.clone()));
+}
+
+#[test]
+fn binomialheap() {
+    let h: BinomialHeap<usize> = Heap::empty();
+    let h2: BinomialHeap<usize> = h.insert(10).insert(9).insert(8).insert(11).insert(1).insert(4);
+
+    assert!(h.is_empty());
+    assert!(!h2.is_empty());
+
+    assert_eq!(h2.find_min(), Some(1));
+    assert_eq!(h2.delete_min(), Some(h.insert(10).insert(9).insert(8).insert(11).insert(4)));
+    assert_eq!(h2.pop_min(), Some((1, h.insert(10).insert(9).insert(8).insert(11).insert(4))));
+
+    assert_eq!(h.find_min(), None);
+    assert_eq!(h.delete_min(), None);
+    assert_eq!(h.pop_min(), None);
+}
+
+#[test]
+fn binomialheap_invariants() {
+    against_vectors::<BinomialHeap<usize>, _>(|h| h.check_invariants());
+}
+
+#[test]
+fn skewbinomialheap() {
+    min_heap::<SkewBinomialHeap<usize>>();
+    against_vectors::<SkewBinomialHeap<usize>, _>(|_| true);
+
+    let h: SkewBinomialHeap<usize> = Heap::empty();
+
+    assert_eq!(h.insert(1).delete_min(), Some(h.clone()));
+}
+
+#[test]
+fn pairingheap() {
+    min_heap::<PairingHeap<usize>>();
+}
+
+#[test]
+fn splayheap() {
+    min_heap::<SplayHeap<usize>>();
+
+    let h: SplayHeap<usize> = Heap::empty();
+
+    // inserting sorted elements splays them into a left spine, which delete_min then halves
+    let h3 = (0..8).fold(h.clone(), |h, x| h.insert(x));
+    let h4 = h3.delete_min().unwrap();
+
+    assert_eq!(h4.find_min(), Some(1));
+    assert_eq!(h4.iter().collect::<Vec<usize>>(), (1..8).collect::<Vec<usize>>());
+}
+
+#[test]
+fn bootstrappedheap() {
+    fn check<F: HeapFamily>() {
+        min_heap::<BootstrappedHeap<usize, F>>();
+
+        let h: BootstrappedHeap<usize, F> = Heap::empty();
+        let xs = vec![10, 9, 8, 11, 1, 4, 12, 4];
+        let h3: BootstrappedHeap<usize, F> = xs.iter().cloned().collect();
+        let h4 = (0..10).fold(h.clone(), |h, i| h.merge(&(0..10).map(|x| x * 7 % 10 * 10 + i).collect()));
+
+        assert_eq!(h3.into_iter().collect::<Vec<usize>>(), vec![1, 4, 4, 8, 9, 10, 11, 12]);
+        assert_eq!(h4.iter().collect::<Vec<usize>>(), (0..100).collect::<Vec<usize>>());
+    }
+
+    check::<LeftistHeapFamily>();
+    check::<BinomialHeapFamily>();
+    check::<SkewBinomialHeapFamily>();
+    check::<PairingHeapFamily>();
+    check::<SplayHeapFamily>();
+}
+
+#[test]
+fn heap_build() {
+    fn check<H: Heap<usize> + Clone>() {
+        let xs: Vec<usize> = (0..1000).map(|x| x * 7919 % 1000 / 2).collect();
+        let mut sorted = xs.clone();
+        sorted.sort();
+
+        let h = H::build(xs.clone());
+
+        assert_eq!(h.find_min(), Some(0));
+        assert_eq!(h.iter().collect::<Vec<usize>>(), sorted);
+        assert_eq!(h.into_sorted_vec(), sorted);
+        assert_eq!(heap_sort::<H, usize>(xs), sorted);
+
+        assert!(H::build(vec![]).is_empty());
+        assert_eq!(H::build(vec![1]).into_sorted_vec(), vec![1]);
+    }
+
+    check::<LeftistHeap<usize>>();
+    check::<WeightBiasedLeftistHeap<usize>>();
+    check::<BinomialHeap<usize>>();
+    check::<SkewBinomialHeap<usize>>();
+    check::<PairingHeap<usize>>();
+    check::<SplayHeap<usize>>();
+    check::<BootstrappedHeap<usize, SkewBinomialHeapFamily>>();
+    check::<ExplicitMin<usize, LeftistHeap<usize>>>();
+
+    // building a leftist heap pairwise keeps it leftist
+    fn valid(h: &LeftistHeap<usize>) -> bool {
+        fn rank(h: &LeftistHeap<usize>) -> usize {
+            match *h { Tip(_) => 0, Node(r, _, _, _) => r }
+        }
+
+        match *h {
+            Node(r, _, ref l, ref rt) => r == rank(rt) + 1 && rank(l) >= rank(rt) && valid(l) && valid(rt),
+            Tip(_) => true
+        }
+    }
+
+    assert!(valid(&LeftistHeap::build(0..100)));
+}
+
+#[test]
+fn heap_iter() {
+    let xs = vec![10, 9, 8, 11, 1, 4, 12, 4];
+    let sorted = vec![1, 4, 4, 8, 9, 10, 11, 12];
+
+    let h: LeftistHeap<usize> = xs.iter().cloned().collect();
+
+    assert_eq!(h.iter().collect::<Vec<usize>>(), sorted);
+    assert_eq!(h.clone().into_iter().collect::<Vec<usize>>(), sorted);
+
+    let mut h2 = h.clone();
+    h2.extend(vec![0, 13]);
+
+    assert_eq!(h2.iter().next(), Some(0));
+    assert_eq!(h2.iter().count(), 10);
+
+    let b: BinomialHeap<usize> = xs.iter().cloned().collect();
+
+    assert_eq!(b.iter().collect::<Vec<usize>>(), sorted);
+    assert_eq!(b.clone().into_iter().collect::<Vec<usize>>(), sorted);
+
+    let mut b2 = b.clone();
+    b2.extend(vec![0, 13]);
 
-    assert_eq!(h2.find_min(), 1);
-    assert_eq!(h2.delete_min(), h.insert(10).insert(9).insert(8).insert(11).insert(4));
+    assert_eq!(b2.iter().next(), Some(0));
+    assert_eq!(b2.iter().count(), 10);
 }
@@ -1,7 +1,38 @@
+pub mod deque;
 pub mod heap;
 pub mod map;
+pub mod order;
+pub mod priority_search_queue;
+pub mod queue;
+pub mod red_black_tree;
 pub mod set;
 pub mod stack;
+pub mod stream;
+pub mod susp;
 pub mod tree;
 pub mod tree_layout;
 pub mod trie;
+pub mod weight_balanced_tree;
+
+// The numbers below `n` in an order that looks random but is the same on every run, to drive the
+// tests that check a structure against a simpler model through long sequences of operations.
+#[cfg(test)]
+fn random(n: usize) -> Random {
+    Random { x: 0, n: n }
+}
+
+#[cfg(test)]
+struct Random {
+    x: usize,
+    n: usize
+}
+
+#[cfg(test)]
+impl Iterator for Random {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        self.x = (self.x * 7919 + 13) % self.n;
+        Some(self.x)
+    }
+}
@@ -1,13 +1,11 @@
 extern crate okasaki;
 
-use std::rc::Rc;
-
 use okasaki::heap::*;
 use okasaki::map::*;
+use okasaki::red_black_tree::RedBlackTree;
 use okasaki::set::*;
 use okasaki::stack::*;
-use okasaki::stack::List::*;
-use okasaki::tree::Tree;
+use okasaki::stack::ListNode::*;
 use okasaki::trie::PatriciaTrie;
 
 fn list() {
@@ -18,24 +16,24 @@ fn list() {
     println!("{:?}", l2.tail());
 
     // Exercise 2.1:
-    // O(n) time and space
-    fn suffixes(l: List<usize>) -> List<List<usize>> {
-        match l {
+    // O(n) time and space, every suffix shares the spine of the original list
+    fn suffixes(l: &List<usize>) -> List<List<usize>> {
+        match **l {
             Nil => {
-                let l: List<List<usize>> = Stack::empty();
-                l.cons(Nil)
+                let ls: List<List<usize>> = Stack::empty();
+                ls.cons(l.clone())
             }
-            Cons(h, ref t) => Cons(Cons(h, t.clone()), Rc::new(suffixes((**t).clone())))
+            Cons(_, ref t) => suffixes(t).cons(l.clone())
         }
     }
 
-    println!("{:?}", suffixes(l.cons(4).cons(3).cons(2).cons(1)));
+    println!("{:?}", suffixes(&l.cons(4).cons(3).cons(2).cons(1)));
 
-    println!("{}", suffixes(l.cons(4).cons(3).cons(2).cons(1)));
+    println!("{}", suffixes(&l.cons(4).cons(3).cons(2).cons(1)));
 }
 
 fn tree() {
-    let t: Tree<usize> = Set::empty();
+    let t: TreeSet<usize> = Set::empty();
     let t2 = t.insert(6).insert(8).insert(9).insert(7)
         .insert(4).insert(5).insert(1);
 
@@ -43,8 +41,17 @@ fn tree() {
     println!("{}", t2);
 }
 
+fn red_black_tree() {
+    let t: RedBlackTree<usize> = Set::empty();
+    let t2 = t.insert(1).insert(2).insert(3).insert(4)
+        .insert(5).insert(6).insert(7);
+
+    println!("{:?}", t2);
+    println!("{}", t2);
+}
+
 fn map() {
-    let m: Tree<(String, usize)> = Map::empty();
+    let m: TreeMap<String, usize> = Map::empty();
     let m2 = m.bind("hello".to_string(), 0)
         .bind("world".to_string(), 1)
         .bind("foo".to_string(), 2)
@@ -94,6 +101,7 @@ fn trie() {
 fn main() {
     list();
     tree();
+    red_black_tree();
     map();
     heap();
     trie();
@@ -1,52 +1,592 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt::{Display, Error, Formatter};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::ops;
 use std::rc::Rc;
 
-use tree::Tree;
-use tree::Tree::{Node, Tip};
+use order::{Natural, Order};
 
 pub trait Map<K, V> {
     fn empty() -> Self;
     fn bind(&self, K, V) -> Self;
-    fn lookup(&self, K) -> V;
+
+    // Like `BTreeMap::get`, lookups take any borrowed form of the keys that the map can be
+    // searched by, e.g. `&str` for `String` keys, so that looking a key up never builds one.
+
+    fn lookup<Q: ?Sized>(&self, k: &Q) -> Option<V> where K: Borrow<Q>, V: Clone, Self: Lookup<V, Q> {
+        self.find(k).cloned()
+    }
+
+    fn unbind<Q: ?Sized>(&self, k: &Q) -> Self where K: Borrow<Q>, Self: Lookup<V, Q> + Sized {
+        self.remove(k)
+    }
+
+    fn insert_with<F>(&self, k: K, v: V, f: F) -> Self
+        where Self: Lookup<V, K> + Sized, V: Clone, F: FnOnce(V, V) -> V {
+        match self.lookup(&k) {
+            Some(old) => self.bind(k, f(v, old)),
+            None => self.bind(k, v)
+        }
+    }
+
+    fn adjust<F>(&self, k: K, f: F) -> Self
+        where Self: Lookup<V, K> + Clone + Sized, V: Clone, F: FnOnce(V) -> V {
+        match self.lookup(&k) {
+            Some(v) => self.bind(k, f(v)),
+            None => self.clone()
+        }
+    }
+
+    fn alter<F>(&self, k: K, f: F) -> Self
+        where Self: Lookup<V, K> + Sized, V: Clone, F: FnOnce(Option<V>) -> Option<V> {
+        match f(self.lookup(&k)) {
+            Some(v) => self.bind(k, v),
+            None => self.unbind(&k)
+        }
+    }
+}
+
+/// Sets and maps that can be searched by `Q`, a borrowed form of their elements or keys. Every
+/// kind of structure puts its own requirements on `Q`: the ordered ones need it to be ordered like
+/// the elements or keys, and the tries need to take it apart. What is found is `T`: the elements
+/// of a set, or the values of a map.
+pub trait Lookup<T, Q: ?Sized> {
+    fn find(&self, &Q) -> Option<&T>;
+    fn remove(&self, &Q) -> Self;
+}
+
+/// An unbalanced binary search tree used as a map. Its nodes keep the key and the value apart,
+/// and only the keys are ever compared, by `O`.
+///
+/// Maps of `Ord` keys and values are `Ord` themselves, so on an owned map `m.min()` is
+/// `Ord::min`; the bindings of the smallest and biggest keys are reached through a reference
+/// instead, as in `(&m).min()`.
+#[derive(Debug)]
+pub enum TreeMap<K, V, O = Natural> {
+    Tip(PhantomData<O>),
+    Node(Rc<TreeMap<K, V, O>>, K, V, Rc<TreeMap<K, V, O>>),
 }
 
-impl<K: Ord + Clone, V: Clone> Map<K, V> for Tree<(K, V)> {
-    fn empty() -> Tree<(K, V)> {
-        Tip
+use map::TreeMap::{Node, Tip};
+
+impl<K: Clone, V: Clone, O> Clone for TreeMap<K, V, O> {
+    fn clone(&self) -> TreeMap<K, V, O> {
+        match *self {
+            Tip(_) => Tip(PhantomData),
+            Node(ref l, ref k, ref v, ref r) => Node(l.clone(), k.clone(), v.clone(), r.clone())
+        }
     }
+}
+
+impl<K: Display, V: Display, O> Display for TreeMap<K, V, O> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        fn aux<K: Display, V: Display, O>(f: &mut Formatter, t: &TreeMap<K, V, O>, right: bool, indent: &str) -> Result<(), Error> {
+            match *t {
+                Node(ref l, ref k, ref v, ref r) => {
+                    try!(aux(f, r, true, &(indent.to_string() + if right { "        " } else { " |      " })));
+
+                    try!(write!(f, "{}", indent));
+                    try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
+                    try!(write!(f, "{}", "----- "));
+
+                    try!(writeln!(f, "({} => {})", k, v));
+
+                    aux(f, l, false, &(indent.to_string() + if right { " |      " } else { "        " }))
+                },
+                Tip(_) => {
+                    try!(write!(f, "{}", indent));
+                    try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
+                    try!(write!(f, "{}", "----- "));
+
+                    writeln!(f, "{}", "()")
+                }
+            }
+        }
 
-    fn bind(&self, k: K, v: V) -> Self {
         match *self {
-            Tip =>
-                Node(Rc::new(Tip), (k, v), Rc::new(Tip)),
-            Node(ref l, (ref k1, ref v1), ref r) if k < *k1 =>
-                Node(Rc::new(l.bind(k, v)), (k1.clone(), v1.clone()), r.clone()),
-            Node(ref l, (ref k1, ref v1), ref r) if k > *k1 =>
-                Node(l.clone(), (k1.clone(), v1.clone()), Rc::new(r.bind(k, v))),
-            _ =>
-                self.clone()
+            Node(ref l, ref k, ref v, ref r) => {
+                try!(aux(f, r, true, ""));
+                try!(writeln!(f, "({} => {})", k, v));
+                aux(f, l, false, "")
+            },
+            Tip(_) => Result::Ok(())
+        }
+    }
+}
+
+// like sets, maps are compared and hashed by their bindings in order of their keys
+
+impl<K: PartialEq, V: PartialEq, O> PartialEq for TreeMap<K, V, O> {
+    fn eq(&self, other: &TreeMap<K, V, O>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Eq, V: Eq, O> Eq for TreeMap<K, V, O> {}
+
+impl<K: PartialOrd, V: PartialOrd, O> PartialOrd for TreeMap<K, V, O> {
+    fn partial_cmp(&self, other: &TreeMap<K, V, O>) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<K: Ord, V: Ord, O> Ord for TreeMap<K, V, O> {
+    fn cmp(&self, other: &TreeMap<K, V, O>) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<K: Hash, V: Hash, O> Hash for TreeMap<K, V, O> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut n = 0;
+
+        for b in self.iter() {
+            b.hash(state);
+            n += 1;
+        }
+
+        state.write_usize(n);
+    }
+}
+
+impl<K, V, O> TreeMap<K, V, O> {
+    /// Builds a perfectly balanced map out of bindings given in order of their keys and without
+    /// duplicate keys, in O(n) time.
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> TreeMap<K, V, O> {
+        fn build<K, V, O, I: Iterator<Item = (K, V)>>(n: usize, it: &mut I) -> TreeMap<K, V, O> {
+            if n == 0 {
+                return Tip(PhantomData);
+            }
+
+            let l = build(n / 2, it);
+            let (k, v) = it.next().unwrap();
+            let r = build(n - n / 2 - 1, it);
+
+            Node(Rc::new(l), k, v, Rc::new(r))
+        }
+
+        let bs: Vec<(K, V)> = iter.into_iter().collect();
+        build(bs.len(), &mut bs.into_iter())
+    }
+
+    /// The value bound to `k`. Unlike `lookup`, this borrows the value, so it does not need to be
+    /// `Clone`.
+    pub fn get<Q: ?Sized>(&self, k: &Q) -> Option<&V> where K: Borrow<Q>, O: Order<Q> {
+        let mut t = self;
+
+        while let Node(ref l, ref k1, ref v, ref r) = *t {
+            if O::lt(k, k1.borrow()) {
+                t = l;
+            } else if O::lt(k1.borrow(), k) {
+                t = r;
+            } else {
+                return Some(v);
+            }
+        }
+
+        None
+    }
+
+    pub fn contains_key<Q: ?Sized>(&self, k: &Q) -> bool where K: Borrow<Q>, O: Order<Q> {
+        self.get(k).is_some()
+    }
+
+    /// The binding of the smallest key.
+    pub fn min(&self) -> Option<(&K, &V)> {
+        let mut t = self;
+        let mut min = None;
+
+        while let Node(ref l, ref k, ref v, _) = *t {
+            min = Some((k, v));
+            t = l;
+        }
+
+        min
+    }
+
+    /// The binding of the biggest key.
+    pub fn max(&self) -> Option<(&K, &V)> {
+        let mut t = self;
+        let mut max = None;
+
+        while let Node(_, ref k, ref v, ref r) = *t {
+            max = Some((k, v));
+            t = r;
+        }
+
+        max
+    }
+
+    /// The binding of the biggest key smaller than `k`.
+    pub fn lookup_lt<Q: ?Sized>(&self, k: &Q) -> Option<(&K, &V)> where K: Borrow<Q>, O: Order<Q> {
+        self.nearest(k, true, |c| c == Ordering::Less)
+    }
+
+    /// The binding of the biggest key smaller than or equal to `k`.
+    pub fn lookup_le<Q: ?Sized>(&self, k: &Q) -> Option<(&K, &V)> where K: Borrow<Q>, O: Order<Q> {
+        self.nearest(k, true, |c| c != Ordering::Greater)
+    }
+
+    /// The binding of the smallest key bigger than `k`.
+    pub fn lookup_gt<Q: ?Sized>(&self, k: &Q) -> Option<(&K, &V)> where K: Borrow<Q>, O: Order<Q> {
+        self.nearest(k, false, |c| c == Ordering::Greater)
+    }
+
+    /// The binding of the smallest key bigger than or equal to `k`.
+    pub fn lookup_ge<Q: ?Sized>(&self, k: &Q) -> Option<(&K, &V)> where K: Borrow<Q>, O: Order<Q> {
+        self.nearest(k, false, |c| c != Ordering::Less)
+    }
+
+    // as for `Tree`, the keys for which `wanted` holds come either all below or all above the
+    // others
+    fn nearest<Q: ?Sized, F: Fn(Ordering) -> bool>(&self, k: &Q, below: bool, wanted: F) -> Option<(&K, &V)>
+        where K: Borrow<Q>, O: Order<Q> {
+        let mut t = self;
+        let mut nearest = None;
+
+        while let Node(ref l, ref k1, ref v, ref r) = *t {
+            if wanted(O::compare(k1.borrow(), k)) {
+                nearest = Some((k1, v));
+                t = if below { r } else { l };
+            } else {
+                t = if below { l } else { r };
+            }
         }
+
+        nearest
+    }
+
+    /// Iterates in order over the bindings whose keys are at least `range.start` and smaller than
+    /// `range.end`, visiting only the subtrees that hold some of them.
+    pub fn range<'a, Q>(&'a self, range: ops::Range<Q>) -> Range<'a, K, V, Q, O> where K: Borrow<Q>, O: Order<Q> {
+        let mut it = Range { stack: vec![], end: range.end };
+        let mut t = self;
+
+        while let Node(ref l, ref k, _, ref r) = *t {
+            if O::lt(k.borrow(), &range.start) {
+                t = r;
+            } else {
+                it.stack.push(t);
+                t = l;
+            }
+        }
+
+        it
     }
 
-    fn lookup(&self, x: K) -> V {
+    /// Iterates over the bindings in order of their keys.
+    pub fn iter<'a>(&'a self) -> Iter<'a, K, V, O> {
+        let mut it = Iter { stack: vec![] };
+        it.push_left(self);
+        it
+    }
+}
+
+impl<K: Clone, V: Clone, O> TreeMap<K, V, O> {
+    // removes the binding of the smallest key, returning it along with the remaining map
+    fn remove_min(&self) -> Option<(K, V, TreeMap<K, V, O>)> {
         match *self {
-            Tip => panic!("element does not exist"),
-            Node(ref l, (ref k, _), _) if x < *k => l.lookup(x),
-            Node(_, (ref k, _), ref r) if x > *k => r.lookup(x),
-            Node(_, (_, ref v), _) => v.clone()
+            Tip(_) => None,
+            Node(ref l, ref k, ref v, ref r) =>
+                match l.remove_min() {
+                    None => Some((k.clone(), v.clone(), (**r).clone())),
+                    Some((k1, v1, l1)) => Some((k1, v1, Node(Rc::new(l1), k.clone(), v.clone(), r.clone())))
+                }
         }
     }
 }
 
+impl<K: Clone, V: Clone, O: Order<K>> Map<K, V> for TreeMap<K, V, O> {
+    fn empty() -> TreeMap<K, V, O> {
+        Tip(PhantomData)
+    }
+
+    fn bind(&self, k: K, v: V) -> TreeMap<K, V, O> {
+        match *self {
+            Tip(_) =>
+                Node(Rc::new(Tip(PhantomData)), k, v, Rc::new(Tip(PhantomData))),
+            Node(ref l, ref k1, ref v1, ref r) if O::lt(&k, k1) =>
+                Node(Rc::new(l.bind(k, v)), k1.clone(), v1.clone(), r.clone()),
+            Node(ref l, ref k1, ref v1, ref r) if O::lt(k1, &k) =>
+                Node(l.clone(), k1.clone(), v1.clone(), Rc::new(r.bind(k, v))),
+            Node(ref l, _, _, ref r) =>
+                Node(l.clone(), k, v, r.clone())
+        }
+    }
+}
+
+impl<K: Clone + Borrow<Q>, V: Clone, Q: ?Sized, O: Order<Q>> Lookup<V, Q> for TreeMap<K, V, O> {
+    fn find(&self, k: &Q) -> Option<&V> {
+        self.get(k)
+    }
+
+    fn remove(&self, k: &Q) -> TreeMap<K, V, O> {
+        match *self {
+            Tip(_) =>
+                Tip(PhantomData),
+            Node(ref l, ref k1, ref v1, ref r) if O::lt(k, k1.borrow()) =>
+                Node(Rc::new(l.remove(k)), k1.clone(), v1.clone(), r.clone()),
+            Node(ref l, ref k1, ref v1, ref r) if O::lt(k1.borrow(), k) =>
+                Node(l.clone(), k1.clone(), v1.clone(), Rc::new(r.remove(k))),
+            Node(ref l, _, _, ref r) =>
+                match r.remove_min() {
+                    None => (**l).clone(),
+                    Some((k1, v1, r1)) => Node(l.clone(), k1, v1, Rc::new(r1))
+                }
+        }
+    }
+}
+
+pub struct Range<'a, K: 'a, V: 'a, Q, O: 'a = Natural> {
+    stack: Vec<&'a TreeMap<K, V, O>>,
+    end: Q
+}
+
+impl<'a, K: Borrow<Q>, V, Q, O: Order<Q>> Iterator for Range<'a, K, V, Q, O> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        match self.stack.pop() {
+            Some(&Node(_, ref k, ref v, ref r)) if O::lt(k.borrow(), &self.end) => {
+                let mut t = &**r;
+
+                while let Node(ref l, _, _, _) = *t {
+                    self.stack.push(t);
+                    t = l;
+                }
+
+                Some((k, v))
+            },
+            _ => {
+                self.stack.clear();
+                None
+            }
+        }
+    }
+}
+
+pub struct Iter<'a, K: 'a, V: 'a, O: 'a = Natural> {
+    stack: Vec<&'a TreeMap<K, V, O>>
+}
+
+impl<'a, K, V, O> Iter<'a, K, V, O> {
+    fn push_left(&mut self, mut t: &'a TreeMap<K, V, O>) {
+        while let Node(ref l, _, _, _) = *t {
+            self.stack.push(t);
+            t = l;
+        }
+    }
+}
+
+impl<'a, K, V, O> Iterator for Iter<'a, K, V, O> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        match self.stack.pop() {
+            Some(&Node(_, ref k, ref v, ref r)) => {
+                self.push_left(r);
+                Some((k, v))
+            },
+            _ => None
+        }
+    }
+}
+
+pub struct IntoIter<K, V, O = Natural> {
+    stack: Vec<Rc<TreeMap<K, V, O>>>
+}
+
+impl<K, V, O> IntoIter<K, V, O> {
+    fn push_left(&mut self, mut t: Rc<TreeMap<K, V, O>>) {
+        loop {
+            let l = match *t {
+                Node(ref l, _, _, _) => l.clone(),
+                Tip(_) => return
+            };
+
+            self.stack.push(t);
+            t = l;
+        }
+    }
+}
+
+impl<K: Clone, V: Clone, O> Iterator for IntoIter<K, V, O> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        match self.stack.pop() {
+            Some(t) =>
+                match *t {
+                    Node(_, ref k, ref v, ref r) => {
+                        self.push_left(r.clone());
+                        Some((k.clone(), v.clone()))
+                    },
+                    Tip(_) => None
+                },
+            None => None
+        }
+    }
+}
+
+impl<'a, K, V, O> IntoIterator for &'a TreeMap<K, V, O> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, O>;
+
+    fn into_iter(self) -> Iter<'a, K, V, O> {
+        self.iter()
+    }
+}
+
+impl<K: Clone, V: Clone, O> IntoIterator for TreeMap<K, V, O> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, O>;
+
+    fn into_iter(self) -> IntoIter<K, V, O> {
+        let mut it = IntoIter { stack: vec![] };
+        it.push_left(Rc::new(self));
+        it
+    }
+}
+
+// Of bindings with equal keys, the last one is kept, as binding them one by one would do: the
+// sort is stable, so reversing the bindings first puts the last one at the front of its run.
+impl<K: Clone, V: Clone, O: Order<K>> FromIterator<(K, V)> for TreeMap<K, V, O> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> TreeMap<K, V, O> {
+        let mut bs: Vec<(K, V)> = iter.into_iter().collect();
+
+        bs.reverse();
+        bs.sort_by(|a, b| O::compare(&a.0, &b.0));
+        bs.dedup_by(|a, b| O::compare(&a.0, &b.0) == Ordering::Equal);
+
+        TreeMap::from_sorted_iter(bs)
+    }
+}
+
+impl<K: Clone, V: Clone, O: Order<K>> Extend<(K, V)> for TreeMap<K, V, O> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        *self = iter.into_iter().fold(self.clone(), |m, (k, v)| m.bind(k, v));
+    }
+}
+
 #[test]
 fn treemap() {
-    let m: Tree<(&str, usize)> = Map::empty();
+    use order::Reverse;
+
+    let m: TreeMap<&str, usize> = Map::empty();
     let m2 = m.bind("hello", 0)
         .bind("world", 1)
         .bind("foo", 2)
         .bind("bar", 3);
 
-    assert_eq!(m2.lookup("hello"), 0);
-    assert_eq!(m2.lookup("world"), 1);
-    assert_eq!(m2.lookup("foo"), 2);
-    assert_eq!(m2.lookup("bar"), 3);
+    assert_eq!(m2.lookup("hello"), Some(0));
+    assert_eq!(m2.lookup("world"), Some(1));
+    assert_eq!(m2.lookup("foo"), Some(2));
+    assert_eq!(m2.lookup("bar"), Some(3));
+
+    assert_eq!(m.lookup("hello"), None);
+    assert_eq!(m2.lookup("baz"), None);
+
+    let m3 = m2.unbind("hello").unbind("bar").unbind("baz");
+
+    assert_eq!(m3.lookup("hello"), None);
+    assert_eq!(m3.lookup("world"), Some(1));
+    assert_eq!(m3.lookup("foo"), Some(2));
+    assert_eq!(m3.lookup("bar"), None);
+    assert_eq!(m2.lookup("hello"), Some(0));
+
+    assert_eq!(m3.unbind("world").unbind("foo"), m);
+
+    let m4 = m2.bind("hello", 4);
+
+    assert_eq!(m4.lookup("hello"), Some(4));
+    assert_eq!(m2.lookup("hello"), Some(0));
+
+    let m5 = m2.insert_with("world", 10, |new, old| new + old)
+        .insert_with("baz", 10, |new, old| new + old)
+        .adjust("foo", |v| v * 10)
+        .adjust("qux", |v| v * 10)
+        .alter("bar", |v| v.map(|v| v + 1))
+        .alter("hello", |_| None)
+        .alter("quux", |_| Some(5));
+
+    assert_eq!(m5.lookup("world"), Some(11));
+    assert_eq!(m5.lookup("baz"), Some(10));
+    assert_eq!(m5.lookup("foo"), Some(20));
+    assert_eq!(m5.lookup("qux"), None);
+    assert_eq!(m5.lookup("bar"), Some(4));
+    assert_eq!(m5.lookup("hello"), None);
+    assert_eq!(m5.lookup("quux"), Some(5));
+
+    let m6: TreeMap<&str, usize> = Map::empty();
+    let m6 = m6.bind("b", 2).bind("c", 3).bind("a", 1);
+
+    assert_eq!(m6.iter().map(|(&k, &v)| (k, v)).collect::<Vec<(&str, usize)>>(), vec![("a", 1), ("b", 2), ("c", 3)]);
+
+    let m7: TreeMap<&str, usize, Reverse> = Map::empty();
+    let m7 = m7.bind("b", 2).bind("c", 3).bind("a", 1).bind("b", 4);
+
+    assert_eq!(m7.iter().map(|(&k, &v)| (k, v)).collect::<Vec<(&str, usize)>>(), vec![("c", 3), ("b", 4), ("a", 1)]);
+    assert_eq!(m7.lookup("a"), Some(1));
+    assert_eq!(m7.unbind("c").lookup("c"), None);
+}
+
+#[test]
+fn treemap_navigation() {
+    let m: TreeMap<usize, &str> = Map::empty();
+    let m = &m.bind(10, "a").bind(30, "c").bind(20, "b").bind(40, "d");
+
+    // the latest entry at or before a time
+    assert_eq!(m.lookup_le(&25), Some((&20, &"b")));
+    assert_eq!(m.lookup_le(&30), Some((&30, &"c")));
+    assert_eq!(m.lookup_le(&5), None);
+    assert_eq!(m.lookup_lt(&30), Some((&20, &"b")));
+    assert_eq!(m.lookup_gt(&30), Some((&40, &"d")));
+    assert_eq!(m.lookup_ge(&31), Some((&40, &"d")));
+
+    assert_eq!(m.min(), Some((&10, &"a")));
+    assert_eq!(m.max(), Some((&40, &"d")));
+    assert_eq!(m.range(15..40).map(|(&k, &v)| (k, v)).collect::<Vec<(usize, &str)>>(), vec![(20, "b"), (30, "c")]);
+
+    let m2: TreeMap<usize, &str> = TreeMap::from_sorted_iter(vec![(10, "a"), (20, "b"), (30, "c"), (40, "d")]);
+
+    assert_eq!(m2.lookup(&30), Some("c"));
+    assert_eq!(m2.lookup_le(&25), Some((&20, &"b")));
+    assert_eq!(m2.iter().collect::<Vec<(&usize, &&str)>>(), m.iter().collect::<Vec<(&usize, &&str)>>());
+}
+
+#[test]
+fn treemap_iter() {
+    let m: TreeMap<&str, usize> = vec![("b", 2), ("a", 1), ("b", 3), ("c", 4)].into_iter().collect();
+
+    assert_eq!(m.iter().map(|(&k, &v)| (k, v)).collect::<Vec<(&str, usize)>>(), vec![("a", 1), ("b", 3), ("c", 4)]);
+    assert_eq!(m.clone().into_iter().collect::<Vec<(&str, usize)>>(), vec![("a", 1), ("b", 3), ("c", 4)]);
+
+    let mut m2 = m.clone();
+    m2.extend(vec![("d", 5), ("a", 0)]);
+
+    assert_eq!(m2.lookup("a"), Some(0));
+    assert_eq!(m2.lookup("d"), Some(5));
+    assert_eq!(m.lookup("d"), None);
+
+    // equal maps are equal whatever their shape, and compare by their bindings
+    let m4: TreeMap<&str, usize> = Map::empty();
+    let m4 = m4.bind("c", 4).bind("b", 3).bind("a", 1);
+
+    assert_eq!(m4, m);
+    assert!(m4.bind("d", 0) > m);
+    assert!(m4.bind("b", 2) < m);
+    assert!(m4.unbind("c") < m);
+
+    // values are only borrowed by `get`, so they need not be `Clone`
+    #[derive(Debug, PartialEq)]
+    struct Opaque(usize);
+
+    let m3: TreeMap<String, Opaque> = TreeMap::from_sorted_iter((0..10).map(|i| (i.to_string(), Opaque(i))));
+
+    assert_eq!(m3.get("7"), Some(&Opaque(7)));
+    assert_eq!(m3.get("10"), None);
+    assert!(m3.contains_key("0"));
 }
@@ -0,0 +1,79 @@
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+
+/// An ordering strategy for the elements of a set, a heap or the keys of a map, playing the part
+/// of the `ORDERED` structure that the functors of the book take as argument. Strategies are
+/// types rather than values, so two structures ordered differently never have the same type.
+pub trait Order<T: ?Sized> {
+    fn compare(&T, &T) -> Ordering;
+
+    fn lt(x: &T, y: &T) -> bool {
+        Self::compare(x, y) == Ordering::Less
+    }
+
+    fn leq(x: &T, y: &T) -> bool {
+        Self::compare(x, y) != Ordering::Greater
+    }
+}
+
+/// The natural ordering of the elements, given by their `Ord` implementation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Natural;
+
+impl<T: Ord + ?Sized> Order<T> for Natural {
+    fn compare(x: &T, y: &T) -> Ordering {
+        x.cmp(y)
+    }
+}
+
+/// The reverse of another ordering, which turns min-heaps into max-heaps.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Reverse<O = Natural>(PhantomData<O>);
+
+impl<T: ?Sized, O: Order<T>> Order<T> for Reverse<O> {
+    fn compare(x: &T, y: &T) -> Ordering {
+        O::compare(y, x)
+    }
+}
+
+/// A function from elements to the keys they are ordered by.
+pub trait Projection<T: ?Sized> {
+    type Key;
+
+    fn key(&T) -> Self::Key;
+}
+
+/// Orders the elements by the keys a projection maps them to, in the given ordering of the keys.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ByKey<P, O = Natural>(PhantomData<(P, O)>);
+
+impl<T: ?Sized, P: Projection<T>, O: Order<P::Key>> Order<T> for ByKey<P, O> {
+    fn compare(x: &T, y: &T) -> Ordering {
+        O::compare(&P::key(x), &P::key(y))
+    }
+}
+
+#[test]
+fn order() {
+    struct Length;
+
+    impl Projection<&'static str> for Length {
+        type Key = usize;
+
+        fn key(x: &&'static str) -> usize {
+            x.len()
+        }
+    }
+
+    assert_eq!(<Natural as Order<usize>>::compare(&1, &2), Ordering::Less);
+    assert_eq!(<Reverse as Order<str>>::compare("a", "b"), Ordering::Greater);
+    assert!(<Natural as Order<usize>>::leq(&2, &2));
+    assert!(!<Natural as Order<usize>>::lt(&2, &2));
+
+    assert_eq!(<Reverse as Order<usize>>::compare(&1, &2), Ordering::Greater);
+    assert_eq!(<Reverse<Reverse> as Order<usize>>::compare(&1, &2), Ordering::Less);
+
+    assert_eq!(<ByKey<Length> as Order<&str>>::compare(&"b", &"aa"), Ordering::Less);
+    assert_eq!(<ByKey<Length> as Order<&str>>::compare(&"b", &"a"), Ordering::Equal);
+    assert_eq!(<ByKey<Length, Reverse> as Order<&str>>::compare(&"b", &"aa"), Ordering::Greater);
+}
@@ -0,0 +1,582 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::cmp::Ordering::{Equal, Greater, Less};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use heap::Heap;
+use map::{Lookup, Map};
+use order::{Natural, Order};
+
+// Hinze, "A Simple Implementation Technique for Priority Search Queues" (2001):
+// a tournament over the bindings ordered by key, where every match is won by the binding of
+// smaller priority. The overall winner sits at the root, and every other binding is kept at the
+// match it lost, whose split key separates the keys of both sides. The loser trees are weight
+// balanced, so that lookup, insertion, deletion and adjust_priority run in O(log n) time,
+// find_min in O(1) time and at_most in O(r log n) time, where r is the number of results.
+// The keys are ordered by KO and the priorities by PO, so that e.g. `PO = Reverse` plays every
+// match for the bigger priority.
+
+#[derive(Clone, Debug)]
+pub enum LoserTree<K, P> {
+    Start,
+    Loser(usize, K, P, Rc<LoserTree<K, P>>, K, Rc<LoserTree<K, P>>),
+}
+
+// the winner of the whole tournament, along with the loser tree and the biggest key; the empty
+// queue carries both orderings, which the loser trees leave out
+#[derive(Debug)]
+pub enum PrioritySearchQueue<K, P, KO = Natural, PO = Natural> {
+    Void(PhantomData<(KO, PO)>),
+    Winner(K, P, Rc<LoserTree<K, P>>, K),
+}
+
+use priority_search_queue::LoserTree::{Start, Loser};
+use priority_search_queue::PrioritySearchQueue::{Void, Winner};
+
+impl<K: Clone, P: Clone, KO, PO> Clone for PrioritySearchQueue<K, P, KO, PO> {
+    fn clone(&self) -> PrioritySearchQueue<K, P, KO, PO> {
+        match *self {
+            Void(_) => Void(PhantomData),
+            Winner(ref k, ref p, ref t, ref m) => Winner(k.clone(), p.clone(), t.clone(), m.clone())
+        }
+    }
+}
+
+// a loser tree is only rebalanced when one of its subtrees is more than OMEGA times bigger than the
+// other one
+const OMEGA: usize = 4;
+
+fn size<K, P>(t: &LoserTree<K, P>) -> usize {
+    match *t {
+        Start => 0,
+        Loser(s, _, _, _, _, _) => s
+    }
+}
+
+fn loser<K, P>(k: K, p: P, l: Rc<LoserTree<K, P>>, m: K, r: Rc<LoserTree<K, P>>) -> Rc<LoserTree<K, P>> {
+    Rc::new(Loser(size(&l) + size(&r) + 1, k, p, l, m, r))
+}
+
+// compares what is searched for with a key, through its borrowed form
+fn search<K: Borrow<Q>, Q: ?Sized, O: Order<Q>>(k: &Q, k1: &K) -> Ordering {
+    O::compare(k, k1.borrow())
+}
+
+// a binding is stored on the side of the split key it came from, so after a rotation the binding
+// that stays at the root is the one that would still lose there
+fn single_left<K: Clone, P: Clone, KO: Order<K>, PO: Order<P>>(k1: K, p1: P, t1: Rc<LoserTree<K, P>>, m1: K,
+                                                               t: &LoserTree<K, P>) -> Rc<LoserTree<K, P>> {
+    match *t {
+        Loser(_, ref k2, ref p2, ref t2, ref m2, ref t3) =>
+            if KO::leq(k2, m2) && (KO::lt(&m1, &k1) || PO::leq(&p1, p2)) {
+                loser(k1, p1, loser(k2.clone(), p2.clone(), t1, m1, t2.clone()), m2.clone(), t3.clone())
+            } else {
+                loser(k2.clone(), p2.clone(), loser(k1, p1, t1, m1, t2.clone()), m2.clone(), t3.clone())
+            },
+        Start => unreachable!()
+    }
+}
+
+fn single_right<K: Clone, P: Clone, KO: Order<K>, PO: Order<P>>(k1: K, p1: P, t: &LoserTree<K, P>, m2: K,
+                                                                t3: Rc<LoserTree<K, P>>) -> Rc<LoserTree<K, P>> {
+    match *t {
+        Loser(_, ref k2, ref p2, ref t1, ref m1, ref t2) =>
+            if KO::lt(m1, k2) && (KO::leq(&k1, &m2) || PO::leq(&p1, p2)) {
+                loser(k1, p1, t1.clone(), m1.clone(), loser(k2.clone(), p2.clone(), t2.clone(), m2, t3))
+            } else {
+                loser(k2.clone(), p2.clone(), t1.clone(), m1.clone(), loser(k1, p1, t2.clone(), m2, t3))
+            },
+        Start => unreachable!()
+    }
+}
+
+fn balance<K: Clone, P: Clone, KO: Order<K>, PO: Order<P>>(k: K, p: P, l: Rc<LoserTree<K, P>>, m: K,
+                                                           r: Rc<LoserTree<K, P>>) -> Rc<LoserTree<K, P>> {
+    if size(&l) + size(&r) < 2 {
+        loser(k, p, l, m, r)
+    } else if size(&r) > OMEGA * size(&l) {
+        match *r {
+            Loser(_, ref k2, ref p2, ref t2, ref m2, ref t3) =>
+                if size(t2) < size(t3) {
+                    single_left::<K, P, KO, PO>(k, p, l, m, &r)
+                } else {
+                    let r1 = single_right::<K, P, KO, PO>(k2.clone(), p2.clone(), t2, m2.clone(), t3.clone());
+                    single_left::<K, P, KO, PO>(k, p, l, m, &r1)
+                },
+            Start => unreachable!()
+        }
+    } else if size(&l) > OMEGA * size(&r) {
+        match *l {
+            Loser(_, ref k2, ref p2, ref t1, ref m1, ref t2) =>
+                if size(t1) > size(t2) {
+                    single_right::<K, P, KO, PO>(k, p, &l, m, r)
+                } else {
+                    let l1 = single_left::<K, P, KO, PO>(k2.clone(), p2.clone(), t1.clone(), m1.clone(), t2);
+                    single_right::<K, P, KO, PO>(k, p, &l1, m, r)
+                },
+            Start => unreachable!()
+        }
+    } else {
+        loser(k, p, l, m, r)
+    }
+}
+
+fn singleton<K: Clone, P, KO, PO>(k: K, p: P) -> PrioritySearchQueue<K, P, KO, PO> {
+    Winner(k.clone(), p, Rc::new(Start), k)
+}
+
+// plays the winners of two tournaments, where every key of the first one is smaller than the keys
+// of the second one
+fn play<K: Clone, P: Clone, KO: Order<K>, PO: Order<P>>(q1: PrioritySearchQueue<K, P, KO, PO>,
+                                                        q2: PrioritySearchQueue<K, P, KO, PO>) -> PrioritySearchQueue<K, P, KO, PO> {
+    match (q1, q2) {
+        (Void(_), q) | (q, Void(_)) => q,
+        (Winner(k1, p1, t1, m1), Winner(k2, p2, t2, m2)) =>
+            if PO::leq(&p1, &p2) {
+                Winner(k1, p1, balance::<K, P, KO, PO>(k2, p2, t1, m1, t2), m2)
+            } else {
+                Winner(k2, p2, balance::<K, P, KO, PO>(k1, p1, t1, m1, t2), m2)
+            }
+    }
+}
+
+// splits a tournament back into the two tournaments whose final was played at the root
+enum TourView<K, P, KO, PO> {
+    Null,
+    Single(K, P),
+    Play(PrioritySearchQueue<K, P, KO, PO>, PrioritySearchQueue<K, P, KO, PO>),
+}
+
+fn tour_view<K: Clone, P: Clone, KO: Order<K>, PO>(q: &PrioritySearchQueue<K, P, KO, PO>) -> TourView<K, P, KO, PO> {
+    match *q {
+        Void(_) => TourView::Null,
+        Winner(ref k, ref p, ref t, ref m) =>
+            match **t {
+                Start => TourView::Single(k.clone(), p.clone()),
+                Loser(_, ref k1, ref p1, ref tl, ref m1, ref tr) =>
+                    if KO::leq(k1, m1) {
+                        TourView::Play(Winner(k1.clone(), p1.clone(), tl.clone(), m1.clone()),
+                                       Winner(k.clone(), p.clone(), tr.clone(), m.clone()))
+                    } else {
+                        TourView::Play(Winner(k.clone(), p.clone(), tl.clone(), m1.clone()),
+                                       Winner(k1.clone(), p1.clone(), tr.clone(), m.clone()))
+                    }
+            }
+    }
+}
+
+type MinView<K, P, KO, PO> = ((K, P), PrioritySearchQueue<K, P, KO, PO>);
+
+fn max_key<K, P, KO, PO>(q: &PrioritySearchQueue<K, P, KO, PO>) -> Option<&K> {
+    match *q {
+        Void(_) => None,
+        Winner(_, _, _, ref m) => Some(m)
+    }
+}
+
+// the tournament between the bindings that lost to the winner, whose biggest key is m
+fn second_best<K: Clone, P: Clone, KO: Order<K>, PO: Order<P>>(t: &LoserTree<K, P>, m: &K) -> PrioritySearchQueue<K, P, KO, PO> {
+    match *t {
+        Start => Void(PhantomData),
+        Loser(_, ref k, ref p, ref tl, ref m1, ref tr) =>
+            if KO::leq(k, m1) {
+                play(Winner(k.clone(), p.clone(), tl.clone(), m1.clone()), second_best(tr, m))
+            } else {
+                play(second_best(tl, m1), Winner(k.clone(), p.clone(), tr.clone(), m.clone()))
+            }
+    }
+}
+
+impl<K: Clone, P: Clone, KO: Order<K>, PO: Order<P>> PrioritySearchQueue<K, P, KO, PO> {
+    pub fn len(&self) -> usize {
+        match *self {
+            Void(_) => 0,
+            Winner(_, _, ref t, _) => size(t) + 1
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match *self {
+            Void(_) => true,
+            _ => false
+        }
+    }
+
+    pub fn delete<Q: ?Sized>(&self, k: &Q) -> PrioritySearchQueue<K, P, KO, PO> where K: Borrow<Q>, KO: Order<Q> {
+        match tour_view(self) {
+            TourView::Null => Void(PhantomData),
+            TourView::Single(ref k1, _) if search::<K, Q, KO>(k, k1) == Equal => Void(PhantomData),
+            TourView::Single(_, _) => self.clone(),
+            TourView::Play(tl, tr) =>
+                if max_key(&tl).map_or(false, |m| search::<K, Q, KO>(k, m) != Greater) {
+                    play(tl.delete(k), tr)
+                } else {
+                    play(tl, tr.delete(k))
+                }
+        }
+    }
+
+    /// Changes the priority bound to the key, if there is any.
+    pub fn adjust_priority<Q: ?Sized, F: FnOnce(P) -> P>(&self, k: &Q, f: F) -> PrioritySearchQueue<K, P, KO, PO>
+        where K: Borrow<Q>, KO: Order<Q> {
+        match tour_view(self) {
+            TourView::Null => Void(PhantomData),
+            TourView::Single(k1, p1) =>
+                if search::<K, Q, KO>(k, &k1) == Equal { singleton(k1, f(p1)) } else { self.clone() },
+            TourView::Play(tl, tr) =>
+                if max_key(&tl).map_or(false, |m| search::<K, Q, KO>(k, m) != Greater) {
+                    play(tl.adjust_priority(k, f), tr)
+                } else {
+                    play(tl, tr.adjust_priority(k, f))
+                }
+        }
+    }
+
+    /// Removes the binding of smallest priority, returning it along with the remaining queue.
+    pub fn min_view(&self) -> Option<MinView<K, P, KO, PO>> {
+        match *self {
+            Void(_) => None,
+            Winner(ref k, ref p, ref t, ref m) => Some(((k.clone(), p.clone()), second_best(t, m)))
+        }
+    }
+
+    /// The bindings whose priority is at most `pt`, in order of their keys. Only the parts of the
+    /// tournament where a binding of small enough priority played are visited.
+    pub fn at_most(&self, pt: &P) -> Vec<(K, P)> {
+        self.bindings(Some(pt))
+    }
+
+    fn bindings(&self, pt: Option<&P>) -> Vec<(K, P)> {
+        fn prune<K: Clone, P: Clone, KO: Order<K>, PO: Order<P>>(k: &K, p: &P, t: &LoserTree<K, P>, pt: Option<&P>,
+                                                                 out: &mut Vec<(K, P)>) {
+            if pt.map_or(true, |pt| PO::leq(p, pt)) {
+                traverse::<K, P, KO, PO>(k, p, t, pt, out);
+            }
+        }
+
+        fn traverse<K: Clone, P: Clone, KO: Order<K>, PO: Order<P>>(k: &K, p: &P, t: &LoserTree<K, P>, pt: Option<&P>,
+                                                                    out: &mut Vec<(K, P)>) {
+            match *t {
+                Start => out.push((k.clone(), p.clone())),
+                Loser(_, ref k1, ref p1, ref tl, ref m, ref tr) =>
+                    if KO::leq(k1, m) {
+                        prune::<K, P, KO, PO>(k1, p1, tl, pt, out);
+                        traverse::<K, P, KO, PO>(k, p, tr, pt, out);
+                    } else {
+                        traverse::<K, P, KO, PO>(k, p, tl, pt, out);
+                        prune::<K, P, KO, PO>(k1, p1, tr, pt, out);
+                    }
+            }
+        }
+
+        let mut out = vec![];
+
+        if let Winner(ref k, ref p, ref t, _) = *self {
+            prune::<K, P, KO, PO>(k, p, t, pt, &mut out);
+        }
+
+        out
+    }
+
+    /// Checks that the keys are ordered by the split keys, that every binding lost to a binding
+    /// of smaller or equal priority, and that the sizes of the loser trees are right.
+    pub fn check_invariants(&self) -> bool {
+        // k and p are the winner of the matches played in t, whose keys lie in (lo, hi]
+        fn valid<K, P, KO: Order<K>, PO: Order<P>>(k: &K, p: &P, t: &LoserTree<K, P>, lo: Option<&K>, hi: &K) -> bool {
+            match *t {
+                Start => lo.map_or(true, |lo| KO::lt(lo, k)) && KO::leq(k, hi),
+                Loser(s, ref k1, ref p1, ref tl, ref m, ref tr) => {
+                    let (wl, wr) = if KO::leq(k1, m) { ((k1, p1), (k, p)) } else { ((k, p), (k1, p1)) };
+
+                    s == size(tl) + size(tr) + 1 && PO::leq(p, p1) && lo.map_or(true, |lo| KO::lt(lo, m)) &&
+                        KO::lt(m, hi) && valid::<K, P, KO, PO>(wl.0, wl.1, tl, lo, m) &&
+                        valid::<K, P, KO, PO>(wr.0, wr.1, tr, Some(m), hi)
+                }
+            }
+        }
+
+        match *self {
+            Void(_) => true,
+            Winner(ref k, ref p, ref t, ref m) => valid::<K, P, KO, PO>(k, p, t, None, m)
+        }
+    }
+}
+
+impl<K: Clone, P: Clone, KO: Order<K>, PO: Order<P>> Map<K, P> for PrioritySearchQueue<K, P, KO, PO> {
+    fn empty() -> PrioritySearchQueue<K, P, KO, PO> {
+        Void(PhantomData)
+    }
+
+    fn bind(&self, k: K, p: P) -> PrioritySearchQueue<K, P, KO, PO> {
+        match tour_view(self) {
+            TourView::Null => singleton(k, p),
+            TourView::Single(k1, p1) =>
+                match KO::compare(&k, &k1) {
+                    Less => play(singleton(k, p), singleton(k1, p1)),
+                    Equal => singleton(k, p),
+                    Greater => play(singleton(k1, p1), singleton(k, p))
+                },
+            TourView::Play(tl, tr) =>
+                if max_key(&tl).map_or(false, |m| KO::leq(&k, m)) {
+                    play(tl.bind(k, p), tr)
+                } else {
+                    play(tl, tr.bind(k, p))
+                }
+        }
+    }
+}
+
+impl<K: Clone + Borrow<Q>, P: Clone, Q: ?Sized, KO: Order<K> + Order<Q>, PO: Order<P>> Lookup<P, Q>
+    for PrioritySearchQueue<K, P, KO, PO> {
+    // follows the split keys down to the match the binding lost, without rebuilding anything
+    fn find(&self, k: &Q) -> Option<&P> {
+        match *self {
+            Void(_) => None,
+            Winner(ref k1, ref p1, ref t, _) => {
+                if search::<K, Q, KO>(k, k1) == Equal {
+                    return Some(p1);
+                }
+
+                let mut t = t;
+
+                while let Loser(_, ref k2, ref p2, ref tl, ref m, ref tr) = **t {
+                    if search::<K, Q, KO>(k, k2) == Equal {
+                        return Some(p2);
+                    }
+
+                    t = if search::<K, Q, KO>(k, m) != Greater { tl } else { tr };
+                }
+
+                None
+            }
+        }
+    }
+
+    fn remove(&self, k: &Q) -> PrioritySearchQueue<K, P, KO, PO> {
+        self.delete(k)
+    }
+}
+
+// Two queues are equal when they bind the same keys to the same priorities, however their
+// tournaments were played. They are ordered and hashed by their bindings in order of the keys.
+
+impl<K: Clone + PartialEq, P: Clone + PartialEq, KO: Order<K>, PO: Order<P>> PartialEq for PrioritySearchQueue<K, P, KO, PO> {
+    fn eq(&self, other: &PrioritySearchQueue<K, P, KO, PO>) -> bool {
+        self.len() == other.len() && self.bindings(None) == other.bindings(None)
+    }
+}
+
+impl<K: Clone + Eq, P: Clone + Eq, KO: Order<K>, PO: Order<P>> Eq for PrioritySearchQueue<K, P, KO, PO> {}
+
+impl<K: Clone + PartialOrd, P: Clone + PartialOrd, KO: Order<K>, PO: Order<P>> PartialOrd for PrioritySearchQueue<K, P, KO, PO> {
+    fn partial_cmp(&self, other: &PrioritySearchQueue<K, P, KO, PO>) -> Option<Ordering> {
+        self.bindings(None).partial_cmp(&other.bindings(None))
+    }
+}
+
+impl<K: Clone + Ord, P: Clone + Ord, KO: Order<K>, PO: Order<P>> Ord for PrioritySearchQueue<K, P, KO, PO> {
+    fn cmp(&self, other: &PrioritySearchQueue<K, P, KO, PO>) -> Ordering {
+        self.bindings(None).cmp(&other.bindings(None))
+    }
+}
+
+impl<K: Clone + Hash, P: Clone + Hash, KO: Order<K>, PO: Order<P>> Hash for PrioritySearchQueue<K, P, KO, PO> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bindings(None).hash(state);
+    }
+}
+
+// the elements of the heap are the bindings, ordered by their priority
+impl<K: Clone, P: Clone, KO: Order<K>, PO: Order<P>> Heap<(K, P)> for PrioritySearchQueue<K, P, KO, PO> {
+    fn empty() -> PrioritySearchQueue<K, P, KO, PO> {
+        Void(PhantomData)
+    }
+
+    fn is_empty(&self) -> bool {
+        PrioritySearchQueue::is_empty(self)
+    }
+
+    // inserts the bindings of the smaller queue into the bigger one, those of h taking precedence
+    // when both queues bind the same key
+    fn merge(&self, h: &PrioritySearchQueue<K, P, KO, PO>) -> PrioritySearchQueue<K, P, KO, PO> {
+        if self.len() >= h.len() {
+            h.bindings(None).into_iter().fold(self.clone(), |q, (k, p)| q.bind(k, p))
+        } else {
+            self.bindings(None).into_iter().fold(h.clone(), |q, (k, p)| {
+                if q.lookup(&k).is_some() { q } else { q.bind(k, p) }
+            })
+        }
+    }
+
+    fn insert(&self, x: (K, P)) -> PrioritySearchQueue<K, P, KO, PO> {
+        self.bind(x.0, x.1)
+    }
+
+    fn find_min(&self) -> Option<(K, P)> {
+        match *self {
+            Void(_) => None,
+            Winner(ref k, ref p, _, _) => Some((k.clone(), p.clone()))
+        }
+    }
+
+    fn delete_min(&self) -> Option<PrioritySearchQueue<K, P, KO, PO>> {
+        self.min_view().map(|(_, q)| q)
+    }
+
+    fn build<I: IntoIterator<Item = (K, P)>>(xs: I) -> PrioritySearchQueue<K, P, KO, PO> {
+        xs.into_iter().collect()
+    }
+}
+
+impl<K: Clone, P: Clone, KO: Order<K>, PO: Order<P>> FromIterator<(K, P)> for PrioritySearchQueue<K, P, KO, PO> {
+    fn from_iter<I: IntoIterator<Item = (K, P)>>(iter: I) -> PrioritySearchQueue<K, P, KO, PO> {
+        iter.into_iter().fold(Void(PhantomData), |q, (k, p)| q.bind(k, p))
+    }
+}
+
+impl<K: Clone, P: Clone, KO: Order<K>, PO: Order<P>> Extend<(K, P)> for PrioritySearchQueue<K, P, KO, PO> {
+    fn extend<I: IntoIterator<Item = (K, P)>>(&mut self, iter: I) {
+        *self = iter.into_iter().fold(self.clone(), |q, (k, p)| q.bind(k, p));
+    }
+}
+
+#[test]
+fn priority_search_queue() {
+    let q: PrioritySearchQueue<&str, usize> = Map::empty();
+    let q2 = q.bind("a", 5).bind("b", 3).bind("c", 8).bind("d", 1).bind("e", 4);
+
+    assert!(q.is_empty());
+    assert_eq!(q2.len(), 5);
+    assert!(q2.check_invariants());
+
+    assert_eq!(q2.lookup("b"), Some(3));
+    assert_eq!(q2.lookup("f"), None);
+    assert_eq!(q2.find_min(), Some(("d", 1)));
+
+    assert_eq!(q2.at_most(&4), vec![("b", 3), ("d", 1), ("e", 4)]);
+    assert_eq!(q2.at_most(&0), vec![]);
+
+    let q3 = q2.adjust_priority("c", |_| 0).delete("d").unbind("z");
+
+    assert_eq!(q3.find_min(), Some(("c", 0)));
+    assert_eq!(q3.lookup("d"), None);
+    assert_eq!(q2.lookup("d"), Some(1));
+    assert_eq!(q3.iter().collect::<Vec<(&str, usize)>>(), vec![("c", 0), ("b", 3), ("e", 4), ("a", 5)]);
+
+    match q3.min_view() {
+        Some((x, q4)) => {
+            assert_eq!(x, ("c", 0));
+            assert_eq!(q4.len(), 3);
+            assert!(q4.check_invariants());
+        },
+        None => panic!()
+    }
+
+    let q5 = q2.merge(&q.bind("a", 0).bind("z", 9));
+
+    assert_eq!(q5.lookup("a"), Some(0));
+    assert_eq!(q5.lookup("z"), Some(9));
+    assert_eq!(q.bind("a", 0).merge(&q2).lookup("a"), Some(5));
+
+    // equal when they bind the same keys to the same priorities, whatever the tournament
+    let q6 = q.bind("e", 4).bind("d", 1).bind("c", 8).bind("b", 3).bind("a", 5);
+
+    assert_eq!(q6, q2);
+    assert!(q6 != q3);
+    assert!(q5 < q2);
+
+    assert_eq!(q.find_min(), None);
+    assert!(q.min_view().is_none());
+    assert!(q.delete_min().is_none());
+}
+
+#[test]
+fn priority_search_queue_orders() {
+    use order::Reverse;
+
+    // a max-priority queue, whose matches are won by the bigger priority
+    let q: PrioritySearchQueue<&str, usize, Natural, Reverse> = Map::empty();
+    let q1 = q.bind("a", 5).bind("b", 3).bind("c", 8).bind("d", 1).bind("e", 4);
+
+    assert!(q1.check_invariants());
+    assert_eq!(q1.find_min(), Some(("c", 8)));
+    assert_eq!(q1.at_most(&4), vec![("a", 5), ("c", 8), ("e", 4)]);
+    assert_eq!(q1.adjust_priority("d", |_| 9).find_min(), Some(("d", 9)));
+    assert_eq!(q1.iter().collect::<Vec<(&str, usize)>>(), vec![("c", 8), ("a", 5), ("e", 4), ("b", 3), ("d", 1)]);
+
+    // keys in reverse order
+    let q: PrioritySearchQueue<&str, usize, Reverse> = Map::empty();
+    let q2 = q.bind("a", 5).bind("b", 3).bind("c", 8).bind("d", 1).bind("e", 4);
+
+    assert!(q2.check_invariants());
+    assert_eq!(q2.find_min(), Some(("d", 1)));
+    assert_eq!(q2.at_most(&4), vec![("e", 4), ("d", 1), ("b", 3)]);
+    assert_eq!(q2.lookup("b"), Some(3));
+    assert_eq!(q2.delete("d").lookup("d"), None);
+    assert!(q2.delete("d").check_invariants());
+}
+
+#[test]
+fn priority_search_queue_invariants() {
+    use std::collections::BTreeMap;
+
+    let mut q: PrioritySearchQueue<usize, usize> = Map::empty();
+    let mut m = BTreeMap::new();
+
+    for x in ::random(1009).take(3000) {
+        let k = x % 200;
+        let p = (x * 31) % 97;
+
+        match x % 5 {
+            0 => { q = q.delete(&k); m.remove(&k); },
+            1 => {
+                q = q.adjust_priority(&k, |p| p / 2);
+                if let Some(v) = m.get_mut(&k) { *v /= 2; }
+            },
+            2 => {
+                let min = m.iter().map(|(k, p)| (*p, *k)).min();
+                assert_eq!(q.find_min().map(|(k, p)| (p, k)), min);
+
+                if let Some((_, q1)) = q.min_view() {
+                    m.remove(&min.unwrap().1);
+                    q = q1;
+                }
+            },
+            _ => { q = q.bind(k, p); m.insert(k, p); }
+        }
+
+        assert!(q.check_invariants());
+        assert_eq!(q.len(), m.len());
+        assert_eq!(q.lookup(&k), m.get(&k).cloned());
+        assert_eq!(q.at_most(&20), m.iter().filter(|&(_, p)| *p <= 20).map(|(k, p)| (*k, *p)).collect::<Vec<(usize, usize)>>());
+    }
+
+    // Dijkstra's shortest paths, decreasing the distance of a vertex every time a shorter path
+    // to it is found
+    let edges = vec![(0, 1, 7), (0, 2, 9), (0, 5, 14), (1, 2, 10), (1, 3, 15), (2, 3, 11), (2, 5, 2), (3, 4, 6), (4, 5, 9)];
+    let mut frontier: PrioritySearchQueue<usize, usize> = Map::empty();
+    let mut dist = vec![None; 6];
+
+    frontier = frontier.bind(0, 0);
+
+    while let Some(((u, d), rest)) = frontier.min_view() {
+        frontier = rest;
+        dist[u] = Some(d);
+
+        for &(a, b, w) in edges.iter() {
+            for &(from, to) in [(a, b), (b, a)].iter() {
+                if from == u && dist[to].is_none() {
+                    frontier = match frontier.lookup(&to) {
+                        Some(d1) if d1 <= d + w => frontier,
+                        _ => frontier.bind(to, d + w)
+                    };
+                }
+            }
+        }
+    }
+
+    assert_eq!(dist, vec![Some(0), Some(7), Some(9), Some(20), Some(20), Some(11)]);
+}
@@ -0,0 +1,206 @@
+use stack::{List, Stack};
+use stream::Stream;
+use stream::StreamCell::{Cons, Nil};
+
+pub trait Queue<T> {
+    fn empty() -> Self;
+    fn is_empty(&self) -> bool;
+
+    fn snoc(&self, T) -> Self;
+    fn head(&self) -> Option<T>;
+    fn tail(&self) -> Option<Self> where Self: Sized;
+}
+
+// Section 5.2:
+// O(1) amortized time for every operation, as long as the queue is used single-threadedly
+#[derive(Clone, Debug)]
+pub struct BatchedQueue<T> {
+    front: List<T>,
+    rear: List<T>
+}
+
+// the front list is only empty when the whole queue is empty
+fn batched<T: Clone>(front: List<T>, rear: List<T>) -> BatchedQueue<T> {
+    if front.is_empty() {
+        BatchedQueue { front: rear.reverse(), rear: Stack::empty() }
+    } else {
+        BatchedQueue { front: front, rear: rear }
+    }
+}
+
+impl<T: Clone> Queue<T> for BatchedQueue<T> {
+    fn empty() -> BatchedQueue<T> {
+        BatchedQueue { front: Stack::empty(), rear: Stack::empty() }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.front.is_empty()
+    }
+
+    fn snoc(&self, x: T) -> BatchedQueue<T> {
+        batched(self.front.clone(), self.rear.cons(x))
+    }
+
+    fn head(&self) -> Option<T> {
+        self.front.head()
+    }
+
+    fn tail(&self) -> Option<BatchedQueue<T>> {
+        self.front.tail().map(|f| batched(f, self.rear.clone()))
+    }
+}
+
+// suspends the reversal of a list until the resulting stream is first forced
+fn reverse<T: Clone + 'static>(l: List<T>) -> Stream<T> {
+    Stream::lazy(move || {
+        let s = l.iter().fold(Stream::empty(), |s: Stream<T>, x| s.cons(x.clone()));
+        let c = s.force().clone();
+        c
+    })
+}
+
+// Section 6.3.2:
+// O(1) amortized time for every operation, even when used persistently, by rotating the rear
+// list onto the front stream as soon as it becomes longer than it
+#[derive(Clone)]
+pub struct BankersQueue<T> {
+    lenf: usize,
+    front: Stream<T>,
+    lenr: usize,
+    rear: List<T>
+}
+
+fn bankers<T: Clone + 'static>(lenf: usize, front: Stream<T>, lenr: usize, rear: List<T>) -> BankersQueue<T> {
+    if lenr <= lenf {
+        BankersQueue { lenf: lenf, front: front, lenr: lenr, rear: rear }
+    } else {
+        BankersQueue { lenf: lenf + lenr, front: front.append(&reverse(rear)), lenr: 0, rear: Stack::empty() }
+    }
+}
+
+impl<T: Clone + 'static> Queue<T> for BankersQueue<T> {
+    fn empty() -> BankersQueue<T> {
+        BankersQueue { lenf: 0, front: Stream::empty(), lenr: 0, rear: Stack::empty() }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.lenf == 0
+    }
+
+    fn snoc(&self, x: T) -> BankersQueue<T> {
+        bankers(self.lenf, self.front.clone(), self.lenr + 1, self.rear.cons(x))
+    }
+
+    fn head(&self) -> Option<T> {
+        self.front.head()
+    }
+
+    fn tail(&self) -> Option<BankersQueue<T>> {
+        self.front.tail().map(|f| bankers(self.lenf - 1, f, self.lenr, self.rear.clone()))
+    }
+}
+
+// Section 7.2:
+// O(1) worst-case time for every operation. The rotation of the rear list onto the front stream
+// is incremental, and every operation forces one more cell of it through the schedule.
+#[derive(Clone)]
+pub struct RealTimeQueue<T> {
+    front: Stream<T>,
+    rear: List<T>,
+    schedule: Stream<T>
+}
+
+// computes f ++ reverse r ++ a one cell at a time, where r is one element longer than f
+fn rotate<T: Clone + 'static>(f: Stream<T>, r: List<T>, a: Stream<T>) -> Stream<T> {
+    Stream::lazy(move || {
+        let (y, r1) = r.uncons().expect("rear list is one element longer than the front stream");
+
+        match *f.force() {
+            Nil => Cons(y, a),
+            Cons(ref x, ref f1) => Cons(x.clone(), rotate(f1.clone(), r1, a.cons(y)))
+        }
+    })
+}
+
+fn exec<T: Clone + 'static>(front: Stream<T>, rear: List<T>, schedule: Stream<T>) -> RealTimeQueue<T> {
+    match schedule.tail() {
+        Some(s) => RealTimeQueue { front: front, rear: rear, schedule: s },
+        None => {
+            let f = rotate(front, rear, Stream::empty());
+            RealTimeQueue { front: f.clone(), rear: Stack::empty(), schedule: f }
+        }
+    }
+}
+
+impl<T: Clone + 'static> Queue<T> for RealTimeQueue<T> {
+    fn empty() -> RealTimeQueue<T> {
+        RealTimeQueue { front: Stream::empty(), rear: Stack::empty(), schedule: Stream::empty() }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.front.is_empty()
+    }
+
+    fn snoc(&self, x: T) -> RealTimeQueue<T> {
+        exec(self.front.clone(), self.rear.cons(x), self.schedule.clone())
+    }
+
+    fn head(&self) -> Option<T> {
+        self.front.head()
+    }
+
+    fn tail(&self) -> Option<RealTimeQueue<T>> {
+        self.front.tail().map(|f| exec(f, self.rear.clone(), self.schedule.clone()))
+    }
+}
+
+#[cfg(test)]
+fn fifo<Q: Queue<usize> + Clone>() {
+    let q: Q = Queue::empty();
+
+    assert!(q.is_empty());
+    assert_eq!(q.head(), None);
+    assert!(q.tail().is_none());
+
+    let q1 = (0..100).fold(q.clone(), |q, x| q.snoc(x));
+
+    assert!(!q1.is_empty());
+
+    let mut q2 = q1.clone();
+
+    for x in 0..50 {
+        assert_eq!(q2.head(), Some(x));
+        q2 = q2.tail().unwrap();
+    }
+
+    let q3 = (100..150).fold(q2.clone(), |q, x| q.snoc(x));
+    let mut q4 = q3.clone();
+
+    for x in 50..150 {
+        assert_eq!(q4.head(), Some(x));
+        q4 = q4.tail().unwrap();
+    }
+
+    assert!(q4.is_empty());
+
+    // older versions are unaffected
+    assert_eq!(q1.head(), Some(0));
+    assert_eq!(q2.head(), Some(50));
+    assert_eq!(q2.snoc(0).head(), Some(50));
+    assert_eq!(q.snoc(1).head(), Some(1));
+}
+
+#[test]
+fn batched_queue() {
+    fifo::<BatchedQueue<usize>>();
+}
+
+#[test]
+fn bankers_queue() {
+    fifo::<BankersQueue<usize>>();
+}
+
+#[test]
+fn real_time_queue() {
+    fifo::<RealTimeQueue<usize>>();
+}
@@ -0,0 +1,686 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt::{Display, Error, Formatter};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use map::Lookup;
+use order::{Natural, Order};
+use set::{Join, Set};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Color {
+    Red,
+    Black,
+}
+
+use red_black_tree::Color::{Red, Black};
+
+// the tip carries the ordering of the tree, like it does for Tree
+#[derive(Debug)]
+pub enum RedBlackTree<T, O = Natural> {
+    Tip(PhantomData<O>),
+    Node(Color, Rc<RedBlackTree<T, O>>, T, Rc<RedBlackTree<T, O>>),
+}
+
+use red_black_tree::RedBlackTree::{Tip, Node};
+
+impl<T: Clone, O> Clone for RedBlackTree<T, O> {
+    fn clone(&self) -> RedBlackTree<T, O> {
+        match *self {
+            Tip(_) => Tip(PhantomData),
+            Node(c, ref l, ref x, ref r) => Node(c, l.clone(), x.clone(), r.clone())
+        }
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            Red => write!(f, "R"),
+            Black => write!(f, "B")
+        }
+    }
+}
+
+impl<T: Display, O> Display for RedBlackTree<T, O> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        fn aux<T: Display, O>(f: &mut Formatter, t: &RedBlackTree<T, O>, right: bool, indent: &str) -> Result<(), Error> {
+            match *t {
+                Node(ref c, ref l, ref x, ref r) => {
+                    try!(aux(f, r, true, &(indent.to_string() + if right { "        " } else { " |      " })));
+
+                    try!(write!(f, "{}", indent));
+                    try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
+                    try!(write!(f, "{}", "----- "));
+
+                    try!(writeln!(f, "({}, {})", c, x));
+
+                    aux(f, l, false, &(indent.to_string() + if right { " |      " } else { "        " }))
+                },
+                Tip(_) => {
+                    try!(write!(f, "{}", indent));
+                    try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
+                    try!(write!(f, "{}", "----- "));
+
+                    writeln!(f, "{}", "()")
+                }
+            }
+        }
+
+        match *self {
+            Node(ref c, ref l, ref x, ref r) => {
+                try!(aux(f, r, true, ""));
+                try!(writeln!(f, "({}, {})", c, x));
+                aux(f, l, false, "")
+            },
+            Tip(_) => Result::Ok(())
+        }
+    }
+}
+
+// the colors are left out, only the elements and their order count
+
+impl<T: PartialEq, O> PartialEq for RedBlackTree<T, O> {
+    fn eq(&self, other: &RedBlackTree<T, O>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, O> Eq for RedBlackTree<T, O> {}
+
+impl<T: PartialOrd, O> PartialOrd for RedBlackTree<T, O> {
+    fn partial_cmp(&self, other: &RedBlackTree<T, O>) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord, O> Ord for RedBlackTree<T, O> {
+    fn cmp(&self, other: &RedBlackTree<T, O>) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash, O> Hash for RedBlackTree<T, O> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut n = 0;
+
+        for x in self.iter() {
+            x.hash(state);
+            n += 1;
+        }
+
+        state.write_usize(n);
+    }
+}
+
+fn black<T: Clone, O>(l: &Rc<RedBlackTree<T, O>>, x: &T, r: &Rc<RedBlackTree<T, O>>) -> Rc<RedBlackTree<T, O>> {
+    Rc::new(Node(Black, l.clone(), x.clone(), r.clone()))
+}
+
+// rewrites a black node with a red child and a red grandchild as a red node with two black
+// children, i.e. the four cases of section 3.3
+fn balance<T: Clone, O>(c: Color, l: Rc<RedBlackTree<T, O>>, x: T, r: Rc<RedBlackTree<T, O>>) -> RedBlackTree<T, O> {
+    if c == Black {
+        if let Node(Red, ref a, ref y, ref b) = *l {
+            if let Node(Red, ref a1, ref y1, ref b1) = **a {
+                return Node(Red, black(a1, y1, b1), y.clone(), black(b, &x, &r));
+            }
+
+            if let Node(Red, ref b1, ref y1, ref c1) = **b {
+                return Node(Red, black(a, y, b1), y1.clone(), black(c1, &x, &r));
+            }
+        }
+
+        if let Node(Red, ref b, ref y, ref c) = *r {
+            if let Node(Red, ref b1, ref y1, ref c1) = **b {
+                return Node(Red, black(&l, &x, b1), y1.clone(), black(c1, y, c));
+            }
+
+            if let Node(Red, ref c1, ref y1, ref d1) = **c {
+                return Node(Red, black(&l, &x, b), y.clone(), black(c1, y1, d1));
+            }
+        }
+    }
+
+    Node(c, l, x, r)
+}
+
+// Deletion as described by Stefan Kahrs in "Red-black trees with types" (2001). Removing a node
+// from a black subtree shortens its black height by one, which `bal_left` and `bal_right` repair
+// on the way back up.
+
+// like `balance`, but for a node that is known to be black and that may also have two red children
+fn bal<T: Clone, O>(l: Rc<RedBlackTree<T, O>>, x: T, r: Rc<RedBlackTree<T, O>>) -> RedBlackTree<T, O> {
+    if let (&Node(Red, ref a, ref y, ref b), &Node(Red, ref c, ref z, ref d)) = (&*l, &*r) {
+        return Node(Red, black(a, y, b), x, black(c, z, d));
+    }
+
+    balance(Black, l, x, r)
+}
+
+fn redden<T: Clone, O>(t: &RedBlackTree<T, O>) -> Rc<RedBlackTree<T, O>> {
+    match *t {
+        Node(Black, ref l, ref x, ref r) => Rc::new(Node(Red, l.clone(), x.clone(), r.clone())),
+        _ => panic!("invariant violation")
+    }
+}
+
+// rebuilds a node whose left subtree is one black node shorter than its right subtree
+fn bal_left<T: Clone, O>(l: Rc<RedBlackTree<T, O>>, x: T, r: Rc<RedBlackTree<T, O>>) -> RedBlackTree<T, O> {
+    if let Node(Red, ref a, ref y, ref b) = *l {
+        return Node(Red, black(a, y, b), x, r.clone());
+    }
+
+    match *r {
+        Node(Black, ref a, ref y, ref b) =>
+            bal(l.clone(), x, Rc::new(Node(Red, a.clone(), y.clone(), b.clone()))),
+        Node(Red, ref a, ref z, ref c) =>
+            match **a {
+                Node(Black, ref a, ref y, ref b) =>
+                    Node(Red, black(&l, &x, a), y.clone(), Rc::new(bal(b.clone(), z.clone(), redden(c)))),
+                _ => panic!("invariant violation")
+            },
+        Tip(_) => panic!("invariant violation")
+    }
+}
+
+// rebuilds a node whose right subtree is one black node shorter than its left subtree
+fn bal_right<T: Clone, O>(l: Rc<RedBlackTree<T, O>>, x: T, r: Rc<RedBlackTree<T, O>>) -> RedBlackTree<T, O> {
+    if let Node(Red, ref b, ref y, ref c) = *r {
+        return Node(Red, l.clone(), x, black(b, y, c));
+    }
+
+    match *l {
+        Node(Black, ref a, ref y, ref b) =>
+            bal(Rc::new(Node(Red, a.clone(), y.clone(), b.clone())), x, r.clone()),
+        Node(Red, ref a, ref y, ref c) =>
+            match **c {
+                Node(Black, ref b, ref z, ref c) =>
+                    Node(Red, Rc::new(bal(redden(a), y.clone(), b.clone())), z.clone(), black(c, &x, &r)),
+                _ => panic!("invariant violation")
+            },
+        Tip(_) => panic!("invariant violation")
+    }
+}
+
+// joins the two subtrees of a deleted node, all elements of `l` being smaller than those of `r`
+fn fuse<T: Clone, O>(l: &Rc<RedBlackTree<T, O>>, r: &Rc<RedBlackTree<T, O>>) -> Rc<RedBlackTree<T, O>> {
+    match (&**l, &**r) {
+        (&Tip(_), _) => r.clone(),
+        (_, &Tip(_)) => l.clone(),
+        (&Node(Red, ref a, ref x, ref b), &Node(Red, ref c, ref y, ref d)) =>
+            match *fuse(b, c) {
+                Node(Red, ref b1, ref z, ref c1) =>
+                    Rc::new(Node(Red,
+                                 Rc::new(Node(Red, a.clone(), x.clone(), b1.clone())),
+                                 z.clone(),
+                                 Rc::new(Node(Red, c1.clone(), y.clone(), d.clone())))),
+                ref bc =>
+                    Rc::new(Node(Red, a.clone(), x.clone(), Rc::new(Node(Red, Rc::new(bc.clone()), y.clone(), d.clone()))))
+            },
+        (&Node(Black, ref a, ref x, ref b), &Node(Black, ref c, ref y, ref d)) =>
+            match *fuse(b, c) {
+                Node(Red, ref b1, ref z, ref c1) =>
+                    Rc::new(Node(Red, black(a, x, b1), z.clone(), black(c1, y, d))),
+                ref bc =>
+                    Rc::new(bal_left(a.clone(), x.clone(), black(&Rc::new(bc.clone()), y, d)))
+            },
+        (_, &Node(Red, ref b, ref x, ref c)) =>
+            Rc::new(Node(Red, fuse(l, b), x.clone(), c.clone())),
+        (&Node(Red, ref a, ref x, ref b), _) =>
+            Rc::new(Node(Red, a.clone(), x.clone(), fuse(b, r)))
+    }
+}
+
+impl<T, O> RedBlackTree<T, O> {
+    /// Checks that no red node has a red child and that every path from the root to a leaf
+    /// contains the same number of black nodes.
+    pub fn check_invariants(&self) -> bool {
+        fn black_height<T, O>(t: &RedBlackTree<T, O>) -> Option<usize> {
+            match *t {
+                Tip(_) => Some(1),
+                Node(c, ref l, _, ref r) => {
+                    if c == Red && (is_red(l) || is_red(r)) {
+                        return None;
+                    }
+
+                    match (black_height(l), black_height(r)) {
+                        (Some(hl), Some(hr)) if hl == hr => Some(if c == Black { hl + 1 } else { hl }),
+                        _ => None
+                    }
+                }
+            }
+        }
+
+        black_height(self).is_some()
+    }
+}
+
+fn is_red<T, O>(t: &RedBlackTree<T, O>) -> bool {
+    match *t {
+        Node(Red, _, _, _) => true,
+        _ => false
+    }
+}
+
+impl<T: Clone, O: Order<T>> Set<T> for RedBlackTree<T, O> {
+    fn empty() -> RedBlackTree<T, O> {
+        Tip(PhantomData)
+    }
+
+    fn insert(&self, x: T) -> RedBlackTree<T, O> {
+        fn ins<T: Clone, O: Order<T>>(t: &RedBlackTree<T, O>, x: T) -> RedBlackTree<T, O> {
+            match *t {
+                Tip(_) => Node(Red, Rc::new(Tip(PhantomData)), x, Rc::new(Tip(PhantomData))),
+                Node(c, ref l, ref y, ref r) if O::lt(&x, y) => balance(c, Rc::new(ins(l, x)), y.clone(), r.clone()),
+                Node(c, ref l, ref y, ref r) if O::lt(y, &x) => balance(c, l.clone(), y.clone(), Rc::new(ins(r, x))),
+                _ => t.clone()
+            }
+        }
+
+        match ins(self, x) {
+            Node(_, l, y, r) => Node(Black, l, y, r),
+            Tip(_) => Tip(PhantomData)
+        }
+    }
+}
+
+impl<T: Clone + Borrow<Q>, Q: ?Sized, O: Order<Q>> Lookup<T, Q> for RedBlackTree<T, O> {
+    fn find(&self, x: &Q) -> Option<&T> {
+        match *self {
+            Tip(_) => None,
+            Node(_, ref l, ref y, _) if O::lt(x, y.borrow()) => l.find(x),
+            Node(_, _, ref y, ref r) if O::lt(y.borrow(), x) => r.find(x),
+            Node(_, _, ref y, _) => Some(y)
+        }
+    }
+
+    fn remove(&self, x: &Q) -> RedBlackTree<T, O> {
+        fn del<T: Clone + Borrow<Q>, Q: ?Sized, O: Order<Q>>(t: &RedBlackTree<T, O>, x: &Q) -> RedBlackTree<T, O> {
+            match *t {
+                Tip(_) => Tip(PhantomData),
+                Node(_, ref l, ref y, ref r) if O::lt(x, y.borrow()) =>
+                    if let Node(Black, _, _, _) = **l {
+                        bal_left(Rc::new(del(l, x)), y.clone(), r.clone())
+                    } else {
+                        Node(Red, Rc::new(del(l, x)), y.clone(), r.clone())
+                    },
+                Node(_, ref l, ref y, ref r) if O::lt(y.borrow(), x) =>
+                    if let Node(Black, _, _, _) = **r {
+                        bal_right(l.clone(), y.clone(), Rc::new(del(r, x)))
+                    } else {
+                        Node(Red, l.clone(), y.clone(), Rc::new(del(r, x)))
+                    },
+                Node(_, ref l, _, ref r) => (*fuse(l, r)).clone()
+            }
+        }
+
+        match del(self, x) {
+            Node(_, l, y, r) => Node(Black, l, y, r),
+            Tip(_) => Tip(PhantomData)
+        }
+    }
+}
+
+fn blacken<T: Clone, O>(t: &RedBlackTree<T, O>) -> RedBlackTree<T, O> {
+    match *t {
+        Node(Red, ref l, ref x, ref r) => Node(Black, l.clone(), x.clone(), r.clone()),
+        _ => t.clone()
+    }
+}
+
+// the number of black nodes on the leftmost path, which is the same for every path
+fn black_height<T, O>(t: &RedBlackTree<T, O>) -> usize {
+    match *t {
+        Tip(_) => 0,
+        Node(c, ref l, _, _) => black_height(l) + if c == Black { 1 } else { 0 }
+    }
+}
+
+// Joining follows the right spine of the taller tree down to a black node as tall as the other
+// tree and hangs both under a new red node there. `balance` then fixes any red node with a red
+// child on the way back up, as it does for insertion.
+
+fn join_right<T: Clone, O>(l: &Rc<RedBlackTree<T, O>>, hl: usize, x: T, r: &Rc<RedBlackTree<T, O>>, hr: usize) -> RedBlackTree<T, O> {
+    match **l {
+        Node(Black, _, _, _) | Tip(_) if hl == hr => Node(Red, l.clone(), x, r.clone()),
+        Node(c, ref a, ref y, ref b) => {
+            let hb = if c == Black { hl - 1 } else { hl };
+            balance(c, a.clone(), y.clone(), Rc::new(join_right(b, hb, x, r, hr)))
+        },
+        Tip(_) => panic!("invariant violation")
+    }
+}
+
+fn join_left<T: Clone, O>(l: &Rc<RedBlackTree<T, O>>, hl: usize, x: T, r: &Rc<RedBlackTree<T, O>>, hr: usize) -> RedBlackTree<T, O> {
+    match **r {
+        Node(Black, _, _, _) | Tip(_) if hl == hr => Node(Red, l.clone(), x, r.clone()),
+        Node(c, ref a, ref y, ref b) => {
+            let ha = if c == Black { hr - 1 } else { hr };
+            balance(c, Rc::new(join_left(l, hl, x, a, ha)), y.clone(), b.clone())
+        },
+        Tip(_) => panic!("invariant violation")
+    }
+}
+
+impl<T: Clone, O: Order<T>> Join<T> for RedBlackTree<T, O> {
+    fn expose(&self) -> Option<(RedBlackTree<T, O>, T, RedBlackTree<T, O>)> {
+        match *self {
+            Tip(_) => None,
+            Node(_, ref l, ref x, ref r) => Some((blacken(l), x.clone(), blacken(r)))
+        }
+    }
+
+    fn split(&self, x: &T) -> (RedBlackTree<T, O>, bool, RedBlackTree<T, O>) {
+        match *self {
+            Tip(_) => (Tip(PhantomData), false, Tip(PhantomData)),
+            Node(_, ref l, ref y, ref r) if O::lt(x, y) => {
+                let (ll, present, lr) = l.split(x);
+                (ll, present, lr.join(y.clone(), &blacken(r)))
+            },
+            Node(_, ref l, ref y, ref r) if O::lt(y, x) => {
+                let (rl, present, rr) = r.split(x);
+                (blacken(l).join(y.clone(), &rl), present, rr)
+            },
+            Node(_, ref l, _, ref r) => (blacken(l), true, blacken(r))
+        }
+    }
+
+    fn join(&self, x: T, r: &RedBlackTree<T, O>) -> RedBlackTree<T, O> {
+        let (l, r) = (Rc::new(blacken(self)), Rc::new(blacken(r)));
+        let (hl, hr) = (black_height(&l), black_height(&r));
+
+        if hl > hr {
+            blacken(&join_right(&l, hl, x, &r, hr))
+        } else if hl < hr {
+            blacken(&join_left(&l, hl, x, &r, hr))
+        } else {
+            Node(Black, l, x, r)
+        }
+    }
+
+    fn same(&self, other: &RedBlackTree<T, O>) -> bool {
+        match (self, other) {
+            (&Tip(_), &Tip(_)) => true,
+            (&Node(_, ref l1, ref x1, ref r1), &Node(_, ref l2, ref x2, ref r2)) =>
+                Rc::ptr_eq(l1, l2) && Rc::ptr_eq(r1, r2) && O::compare(x1, x2) == Ordering::Equal,
+            _ => false
+        }
+    }
+}
+
+impl<T, O> RedBlackTree<T, O> {
+    /// Iterates over the elements of the tree in order.
+    pub fn iter<'a>(&'a self) -> Iter<'a, T, O> {
+        let mut it = Iter { stack: vec![] };
+        it.push_left(self);
+        it
+    }
+}
+
+pub struct Iter<'a, T: 'a, O: 'a> {
+    stack: Vec<&'a RedBlackTree<T, O>>
+}
+
+impl<'a, T, O> Iter<'a, T, O> {
+    fn push_left(&mut self, mut t: &'a RedBlackTree<T, O>) {
+        while let Node(_, ref l, _, _) = *t {
+            self.stack.push(t);
+            t = l;
+        }
+    }
+}
+
+impl<'a, T, O> Iterator for Iter<'a, T, O> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        match self.stack.pop() {
+            Some(&Node(_, _, ref x, ref r)) => {
+                self.push_left(r);
+                Some(x)
+            },
+            _ => None
+        }
+    }
+}
+
+pub struct IntoIter<T, O> {
+    stack: Vec<Rc<RedBlackTree<T, O>>>
+}
+
+impl<T, O> IntoIter<T, O> {
+    fn push_left(&mut self, mut t: Rc<RedBlackTree<T, O>>) {
+        loop {
+            let l = match *t {
+                Node(_, ref l, _, _) => l.clone(),
+                Tip(_) => return
+            };
+
+            self.stack.push(t);
+            t = l;
+        }
+    }
+}
+
+impl<T: Clone, O> Iterator for IntoIter<T, O> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match self.stack.pop() {
+            Some(t) =>
+                match *t {
+                    Node(_, _, ref x, ref r) => {
+                        self.push_left(r.clone());
+                        Some(x.clone())
+                    },
+                    Tip(_) => None
+                },
+            None => None
+        }
+    }
+}
+
+impl<'a, T, O> IntoIterator for &'a RedBlackTree<T, O> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, O>;
+
+    fn into_iter(self) -> Iter<'a, T, O> {
+        self.iter()
+    }
+}
+
+impl<T: Clone, O> IntoIterator for RedBlackTree<T, O> {
+    type Item = T;
+    type IntoIter = IntoIter<T, O>;
+
+    fn into_iter(self) -> IntoIter<T, O> {
+        let mut it = IntoIter { stack: vec![] };
+        it.push_left(Rc::new(self));
+        it
+    }
+}
+
+impl<T, O> RedBlackTree<T, O> {
+    // Exercise 3.9:
+    // builds a tree out of elements given in order and without duplicates in O(n) time. Splitting
+    // the elements evenly fills every level but the last one, whose nodes are coloured red so
+    // that every path holds as many black nodes as there are full levels.
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> RedBlackTree<T, O> {
+        fn build<T, O, I: Iterator<Item = T>>(n: usize, depth: usize, red: usize, it: &mut I) -> RedBlackTree<T, O> {
+            if n == 0 {
+                return Tip(PhantomData);
+            }
+
+            let l = build(n / 2, depth + 1, red, it);
+            let x = it.next().unwrap();
+            let r = build(n - n / 2 - 1, depth + 1, red, it);
+
+            Node(if depth == red { Red } else { Black }, Rc::new(l), x, Rc::new(r))
+        }
+
+        let xs: Vec<T> = iter.into_iter().collect();
+        let mut full = 0;
+
+        while (1 << (full + 1)) - 1 <= xs.len() {
+            full += 1;
+        }
+
+        build(xs.len(), 0, full, &mut xs.into_iter())
+    }
+}
+
+impl<T: Clone, O: Order<T>> FromIterator<T> for RedBlackTree<T, O> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> RedBlackTree<T, O> {
+        let mut xs: Vec<T> = iter.into_iter().collect();
+
+        xs.sort_by(O::compare);
+        xs.dedup_by(|x, y| O::compare(x, y) == Ordering::Equal);
+
+        RedBlackTree::from_sorted_iter(xs)
+    }
+}
+
+impl<T: Clone, O: Order<T>> Extend<T> for RedBlackTree<T, O> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        *self = iter.into_iter().fold(self.clone(), |t, x| t.insert(x));
+    }
+}
+
+#[test]
+fn redblacktree() {
+    let t: RedBlackTree<usize> = Set::empty();
+    let t2 = t.insert(1).insert(2).insert(3);
+    let tip = Rc::new(t.clone());
+
+    // equality ignores the colors and the shape, but the debug output shows them
+    assert_eq!(format!("{:?}", t2),
+               format!("{:?}", Node(Black,
+                   Rc::new(Node(Black, tip.clone(), 1, tip.clone())),
+                   2,
+                   Rc::new(Node(Black, tip.clone(), 3, tip.clone())))));
+
+    let t3 = (0..1000).fold(t.clone(), |t, x| t.insert(x));
+
+    assert!(t3.check_invariants());
+    assert!((0..1000).all(|x| t3.member(&x)));
+    assert!(!t3.member(&1000));
+
+    let t4 = (0..1000).rev().fold(t.clone(), |t, x| t.insert((x * 7919) % 1000));
+
+    assert!(t4.check_invariants());
+    assert!((0..1000).all(|x| t4.member(&x)));
+
+    let mut t5 = t4.clone();
+    let mut present = vec![true; 1000];
+
+    for x in ::random(1000).take(1500) {
+        t5 = t5.delete(&x);
+        present[x] = false;
+
+        assert!(t5.check_invariants());
+        assert!((0..1000).all(|y| t5.member(&y) == present[y]));
+    }
+
+    assert!((0..1000).all(|y| t4.member(&y)));
+
+    assert!(!Node(Red, Rc::new(Node(Red, tip.clone(), 1, tip.clone())), 2, tip.clone()).check_invariants());
+    assert!(!Node(Black, Rc::new(Node(Black, tip.clone(), 1, tip.clone())), 2, tip.clone()).check_invariants());
+}
+
+#[test]
+fn redblacktree_iter() {
+    use order::Reverse;
+
+    let t: RedBlackTree<usize> = (0..100).rev().collect();
+
+    assert!(t.check_invariants());
+    assert_eq!(t.iter().cloned().collect::<Vec<usize>>(), (0..100).collect::<Vec<usize>>());
+    assert_eq!((&t).into_iter().count(), 100);
+    assert_eq!(t.clone().into_iter().collect::<Vec<usize>>(), (0..100).collect::<Vec<usize>>());
+
+    let mut t2 = t.clone();
+    t2.extend(vec![150, 100, 50]);
+
+    assert!(t2.check_invariants());
+    assert_eq!(t2.clone().into_iter().skip(99).collect::<Vec<usize>>(), vec![99, 100, 150]);
+
+    // equal whatever the order of insertion, and with it the colors and the shape
+    let u: RedBlackTree<usize> = (0..100).fold(Set::empty(), |t, x| t.insert(x));
+
+    assert_eq!(t, u);
+    assert!(t < t2);
+    assert!(t2 < t.delete(&0));
+    assert!(t != t.delete(&50));
+
+    let r: RedBlackTree<usize, Reverse> = (0..100).collect();
+
+    assert!(r.check_invariants() && r.delete(&50).check_invariants());
+    assert_eq!(r.iter().cloned().take(3).collect::<Vec<usize>>(), vec![99, 98, 97]);
+    assert!(r.member(&50) && !r.delete(&50).member(&50));
+}
+
+#[test]
+fn redblacktree_algebra() {
+    let t: RedBlackTree<usize> = (0..1000).map(|x| (x * 7919) % 1000).filter(|x| x % 3 != 0).collect();
+    let u: RedBlackTree<usize> = (0..1000).filter(|x| x % 5 != 0).collect();
+    let small: RedBlackTree<usize> = vec![3, 500, 997, 1500].into_iter().collect();
+
+    let elems = |t: &RedBlackTree<usize>| t.iter().cloned().collect::<Vec<usize>>();
+
+    for &(ref a, ref b) in &[(&t, &u), (&u, &t), (&t, &small), (&small, &u)] {
+        let model = |f: fn(bool, bool) -> bool| (0..2000).filter(|&x| f(a.member(&x), b.member(&x))).collect::<Vec<usize>>();
+
+        let union = a.union(b);
+        let intersection = a.intersection(b);
+        let difference = a.difference(b);
+
+        assert!(union.check_invariants() && intersection.check_invariants() && difference.check_invariants());
+        assert!(!is_red(&union) && !is_red(&intersection) && !is_red(&difference));
+
+        assert_eq!(elems(&union), model(|x, y| x || y));
+        assert_eq!(elems(&intersection), model(|x, y| x && y));
+        assert_eq!(elems(&difference), model(|x, y| x && !y));
+
+        assert!(intersection.is_subset(a) && intersection.is_subset(b));
+        assert!(difference.is_disjoint(b));
+        assert_eq!(a.is_subset(b), model(|x, y| x && !y).is_empty());
+        assert_eq!(a.is_disjoint(b), model(|x, y| x && y).is_empty());
+    }
+
+    for x in 0..1000 {
+        let (l, present, r) = t.split(&x);
+
+        assert!(l.check_invariants() && r.check_invariants());
+        assert_eq!(present, x % 3 != 0);
+        assert!(l.iter().all(|&y| y < x) && r.iter().all(|&y| y > x));
+        assert_eq!(l.iter().count() + r.iter().count() + present as usize, 666);
+    }
+
+    let bigger = (0..100).fold(t.clone(), |t, x| t.insert(x * 3));
+    let smaller = (0..100).fold(t.clone(), |t, x| t.delete(&(x * 7)));
+
+    assert!(smaller.is_subset(&t) && t.is_subset(&bigger));
+    assert_eq!(bigger.difference(&smaller).iter().count(), 100 + t.difference(&smaller).iter().count());
+    assert_eq!(elems(&bigger.intersection(&smaller)), elems(&smaller));
+}
+
+#[test]
+fn redblacktree_bulk() {
+    for n in 0..300 {
+        let t: RedBlackTree<usize> = RedBlackTree::from_sorted_iter(0..n);
+
+        assert!(t.check_invariants());
+        assert!(!is_red(&t));
+        assert_eq!(t.iter().cloned().collect::<Vec<usize>>(), (0..n).collect::<Vec<usize>>());
+    }
+
+    let t: RedBlackTree<usize> = (0..1000).map(|x| (x * 7919) % 500).collect();
+
+    assert!(t.check_invariants());
+    assert_eq!(t.iter().cloned().collect::<Vec<usize>>(), (0..500).collect::<Vec<usize>>());
+    assert!(t.insert(1000).delete(&3).check_invariants());
+}
@@ -1,75 +1,676 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt::{Display, Error, Formatter};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::ops;
 use std::rc::Rc;
 
-use tree::Tree;
+use map::Lookup;
+use order::{Natural, Order};
+use tree::{IntoIter, Iter, Range, Tree};
 use tree::Tree::{Node, Tip};
 
 pub trait Set<T> {
     fn empty() -> Self;
     fn insert(&self, T) -> Self;
-    fn member(&self, T) -> bool;
-}
 
-impl<T: Ord + Clone> Set<T> for Tree<T> {
-    fn empty() -> Tree<T> {
-        Tip
+    // like `BTreeSet::contains`, these take any borrowed form of the elements that the set can be
+    // searched by, e.g. `&str` for a set of `String`s
+
+    fn member<Q: ?Sized>(&self, x: &Q) -> bool where T: Borrow<Q>, Self: Lookup<T, Q> {
+        self.find(x).is_some()
+    }
+
+    fn delete<Q: ?Sized>(&self, x: &Q) -> Self where T: Borrow<Q>, Self: Lookup<T, Q> + Sized {
+        self.remove(x)
+    }
+
+    // The set algebra follows Blelloch, Ferizovic and Sun, "Just Join for Parallel Ordered Sets"
+    // (2016): the second set is taken apart at its root, and the first one is split around it.
+    // On balanced trees this takes O(m log(n/m + 1)) time, where m is the size of the smaller
+    // set, and subtrees that both sets share are never looked into.
+
+    fn union(&self, other: &Self) -> Self where Self: Join<T> {
+        if self.same(other) {
+            return self.clone();
+        }
+
+        match other.expose() {
+            None => self.clone(),
+            Some((l2, x, r2)) => {
+                if self.expose().is_none() {
+                    return other.clone();
+                }
+
+                let (l1, _, r1) = self.split(&x);
+                l1.union(&l2).join(x, &r1.union(&r2))
+            }
+        }
     }
 
-    fn insert(&self, x: T) -> Tree<T> {
-        match *self {
-            Tip => Node(Rc::new(Tip), x, Rc::new(Tip)),
-            Node(ref l, ref v, ref r) if x < *v => Node(Rc::new(l.insert(x)), v.clone(), r.clone()),
-            Node(ref l, ref v, ref r) if x > *v => Node(l.clone(), v.clone(), Rc::new(r.insert(x))),
+    fn intersection(&self, other: &Self) -> Self where Self: Join<T> {
+        if self.same(other) {
+            return self.clone();
+        }
+
+        match (self.expose(), other.expose()) {
+            (Some(_), Some((l2, x, r2))) => {
+                let (l1, present, r1) = self.split(&x);
+                let (l, r) = (l1.intersection(&l2), r1.intersection(&r2));
+
+                if present { l.join(x, &r) } else { l.join2(&r) }
+            },
+            _ => Set::empty()
+        }
+    }
+
+    fn difference(&self, other: &Self) -> Self where Self: Join<T> {
+        if self.same(other) {
+            return Set::empty();
+        }
+
+        match (self.expose(), other.expose()) {
+            (Some(_), Some((l2, x, r2))) => {
+                let (l1, _, r1) = self.split(&x);
+                l1.difference(&l2).join2(&r1.difference(&r2))
+            },
             _ => self.clone()
         }
     }
 
-    fn member(&self, x: T) -> bool {
-        match *self {
-            Tip => false,
-            Node(ref l, ref v, _) if x < *v => l.member(x),
-            Node(_, ref v, ref r) if x > *v => r.member(x),
+    fn is_subset(&self, other: &Self) -> bool where Self: Join<T> {
+        if self.same(other) {
+            return true;
+        }
+
+        match self.expose() {
+            None => true,
+            Some((l1, x, r1)) => {
+                let (l2, present, r2) = other.split(&x);
+                present && l1.is_subset(&l2) && r1.is_subset(&r2)
+            }
+        }
+    }
+
+    fn is_disjoint(&self, other: &Self) -> bool where Self: Join<T> {
+        match (self.expose(), other.expose()) {
+            (Some(_), Some((l2, x, r2))) => {
+                if self.same(other) {
+                    return false;
+                }
+
+                let (l1, present, r1) = self.split(&x);
+                !present && l1.is_disjoint(&l2) && r1.is_disjoint(&r2)
+            },
             _ => true
         }
     }
 }
 
+/// Sets kept as search trees, which can be taken apart at their root, split around an element
+/// and joined back together. These are all that the set algebra of `Set` needs.
+pub trait Join<T>: Clone {
+    /// The left subtree, the root and the right subtree of a non-empty tree.
+    fn expose(&self) -> Option<(Self, T, Self)>;
+
+    /// The elements smaller than `x`, whether `x` is in the set, and the elements bigger than it.
+    fn split(&self, x: &T) -> (Self, bool, Self);
+
+    /// The union of the two sets and `x`, where every element of `self` is smaller than `x` and
+    /// every element of `r` is bigger than it.
+    fn join(&self, x: T, r: &Self) -> Self;
+
+    /// Whether both trees are known to be the same, because they share their subtrees and have
+    /// the same root.
+    fn same(&self, other: &Self) -> bool;
+
+    /// The union of the two sets, where every element of `self` is smaller than the elements of
+    /// `r`.
+    fn join2(&self, r: &Self) -> Self {
+        match self.expose() {
+            None => r.clone(),
+            Some((l, x, m)) => l.join(x, &m.join2(r))
+        }
+    }
+}
+
+/// An unbalanced binary search tree used as a set, whose elements are kept sorted by `O`.
+///
+/// Sets of `Ord` elements are `Ord` themselves, so on an owned set `s.min()` is `Ord::min`; the
+/// smallest and biggest elements are reached through a reference instead, as in `(&s).min()`.
+#[derive(Debug)]
+pub struct TreeSet<T, O = Natural>(Tree<T, O>);
+
+impl<T: Clone, O> Clone for TreeSet<T, O> {
+    fn clone(&self) -> TreeSet<T, O> {
+        TreeSet(self.0.clone())
+    }
+}
+
+impl<T: Display, O> Display for TreeSet<T, O> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        self.0.fmt(f)
+    }
+}
+
+// Sets are compared and hashed by their elements in order, whatever the shape of their trees, so
+// that sets built in different orders are equal, and sets can be elements of other sets.
+
+impl<T: PartialEq, O> PartialEq for TreeSet<T, O> {
+    fn eq(&self, other: &TreeSet<T, O>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, O> Eq for TreeSet<T, O> {}
+
+impl<T: PartialOrd, O> PartialOrd for TreeSet<T, O> {
+    fn partial_cmp(&self, other: &TreeSet<T, O>) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord, O> Ord for TreeSet<T, O> {
+    fn cmp(&self, other: &TreeSet<T, O>) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash, O> Hash for TreeSet<T, O> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut n = 0;
+
+        for x in self.iter() {
+            x.hash(state);
+            n += 1;
+        }
+
+        state.write_usize(n);
+    }
+}
+
+impl<T, O> TreeSet<T, O> {
+    /// The tree holding the elements, e.g. for laying it out.
+    pub fn as_tree(&self) -> &Tree<T, O> {
+        &self.0
+    }
+
+    /// Builds a perfectly balanced set out of elements given in order and without duplicates, in
+    /// O(n) time.
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> TreeSet<T, O> {
+        TreeSet(Tree::from_sorted_iter(iter))
+    }
+
+    /// Iterates over the elements of the set in order.
+    pub fn iter<'a>(&'a self) -> Iter<'a, T, O> {
+        self.0.iter()
+    }
+
+    /// The smallest element of the set.
+    pub fn min(&self) -> Option<&T> {
+        self.0.min()
+    }
+
+    /// The biggest element of the set.
+    pub fn max(&self) -> Option<&T> {
+        self.0.max()
+    }
+
+    /// The biggest element smaller than `q`.
+    pub fn lookup_lt<Q: ?Sized>(&self, q: &Q) -> Option<&T> where T: Borrow<Q>, O: Order<Q> {
+        self.0.lookup_lt(q)
+    }
+
+    /// The biggest element smaller than or equal to `q`.
+    pub fn lookup_le<Q: ?Sized>(&self, q: &Q) -> Option<&T> where T: Borrow<Q>, O: Order<Q> {
+        self.0.lookup_le(q)
+    }
+
+    /// The smallest element bigger than `q`.
+    pub fn lookup_gt<Q: ?Sized>(&self, q: &Q) -> Option<&T> where T: Borrow<Q>, O: Order<Q> {
+        self.0.lookup_gt(q)
+    }
+
+    /// The smallest element bigger than or equal to `q`.
+    pub fn lookup_ge<Q: ?Sized>(&self, q: &Q) -> Option<&T> where T: Borrow<Q>, O: Order<Q> {
+        self.0.lookup_ge(q)
+    }
+
+    /// Iterates in order over the elements that are at least `range.start` and smaller than
+    /// `range.end`.
+    pub fn range<'a, Q>(&'a self, range: ops::Range<Q>) -> Range<'a, T, Q, O> where T: Borrow<Q>, O: Order<Q> {
+        self.0.range(range)
+    }
+
+    /// Looks `x` up with at most d + 1 comparisons, where d is the depth of the tree (Exercise 2.2).
+    pub fn member2<Q: ?Sized>(&self, x: &Q) -> bool where T: Borrow<Q>, O: Order<Q> {
+        self.0.member2(x)
+    }
+}
+
+impl<T: Clone, O> TreeSet<T, O> {
+    /// Removes the smallest element of the set, returning it along with the remaining set.
+    pub fn remove_min(&self) -> Option<(T, TreeSet<T, O>)> {
+        self.0.remove_min().map(|(x, t)| (x, TreeSet(t)))
+    }
+}
+
+impl<T: Clone, O: Order<T>> Set<T> for TreeSet<T, O> {
+    fn empty() -> TreeSet<T, O> {
+        TreeSet(Tip(PhantomData))
+    }
+
+    fn insert(&self, x: T) -> TreeSet<T, O> {
+        fn ins<T: Clone, O: Order<T>>(t: &Tree<T, O>, x: T) -> Tree<T, O> {
+            match *t {
+                Tip(_) => Node(Rc::new(Tip(PhantomData)), x, Rc::new(Tip(PhantomData))),
+                Node(ref l, ref v, ref r) if O::lt(&x, v) => Node(Rc::new(ins(l, x)), v.clone(), r.clone()),
+                Node(ref l, ref v, ref r) if O::lt(v, &x) => Node(l.clone(), v.clone(), Rc::new(ins(r, x))),
+                _ => t.clone()
+            }
+        }
+
+        TreeSet(ins(&self.0, x))
+    }
+}
+
+impl<T: Clone + Borrow<Q>, Q: ?Sized, O: Order<Q>> Lookup<T, Q> for TreeSet<T, O> {
+    fn find(&self, x: &Q) -> Option<&T> {
+        let mut t = &self.0;
+
+        while let Node(ref l, ref v, ref r) = *t {
+            if O::lt(x, v.borrow()) {
+                t = l;
+            } else if O::lt(v.borrow(), x) {
+                t = r;
+            } else {
+                return Some(v);
+            }
+        }
+
+        None
+    }
+
+    fn remove(&self, x: &Q) -> TreeSet<T, O> {
+        fn del<T: Clone + Borrow<Q>, Q: ?Sized, O: Order<Q>>(t: &Tree<T, O>, x: &Q) -> Tree<T, O> {
+            match *t {
+                Tip(_) => Tip(PhantomData),
+                Node(ref l, ref v, ref r) if O::lt(x, v.borrow()) => Node(Rc::new(del(l, x)), v.clone(), r.clone()),
+                Node(ref l, ref v, ref r) if O::lt(v.borrow(), x) => Node(l.clone(), v.clone(), Rc::new(del(r, x))),
+                Node(ref l, _, ref r) =>
+                    match r.remove_min() {
+                        None => (**l).clone(),
+                        Some((m, r1)) => Node(l.clone(), m, Rc::new(r1))
+                    }
+            }
+        }
+
+        TreeSet(del(&self.0, x))
+    }
+}
+
+// the tree is not balanced, so joining simply puts the element on top
+impl<T: Clone, O: Order<T>> Join<T> for TreeSet<T, O> {
+    fn expose(&self) -> Option<(TreeSet<T, O>, T, TreeSet<T, O>)> {
+        match self.0 {
+            Tip(_) => None,
+            Node(ref l, ref x, ref r) => Some((TreeSet((**l).clone()), x.clone(), TreeSet((**r).clone())))
+        }
+    }
+
+    fn split(&self, x: &T) -> (TreeSet<T, O>, bool, TreeSet<T, O>) {
+        fn split<T: Clone, O: Order<T>>(t: &Tree<T, O>, x: &T) -> (Tree<T, O>, bool, Tree<T, O>) {
+            match *t {
+                Tip(_) => (Tip(PhantomData), false, Tip(PhantomData)),
+                Node(ref l, ref v, ref r) if O::lt(x, v) => {
+                    let (ll, present, lr) = split(l, x);
+                    (ll, present, Node(Rc::new(lr), v.clone(), r.clone()))
+                },
+                Node(ref l, ref v, ref r) if O::lt(v, x) => {
+                    let (rl, present, rr) = split(r, x);
+                    (Node(l.clone(), v.clone(), Rc::new(rl)), present, rr)
+                },
+                Node(ref l, _, ref r) => ((**l).clone(), true, (**r).clone())
+            }
+        }
+
+        let (l, present, r) = split(&self.0, x);
+        (TreeSet(l), present, TreeSet(r))
+    }
+
+    fn join(&self, x: T, r: &TreeSet<T, O>) -> TreeSet<T, O> {
+        TreeSet(Node(Rc::new(self.0.clone()), x, Rc::new(r.0.clone())))
+    }
+
+    fn same(&self, other: &TreeSet<T, O>) -> bool {
+        match (&self.0, &other.0) {
+            (&Tip(_), &Tip(_)) => true,
+            (&Node(ref l1, ref x1, ref r1), &Node(ref l2, ref x2, ref r2)) =>
+                Rc::ptr_eq(l1, l2) && Rc::ptr_eq(r1, r2) && O::compare(x1, x2) == Ordering::Equal,
+            _ => false
+        }
+    }
+}
+
+impl<'a, T, O> IntoIterator for &'a TreeSet<T, O> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, O>;
+
+    fn into_iter(self) -> Iter<'a, T, O> {
+        self.0.iter()
+    }
+}
+
+impl<T: Clone, O> IntoIterator for TreeSet<T, O> {
+    type Item = T;
+    type IntoIter = IntoIter<T, O>;
+
+    fn into_iter(self) -> IntoIter<T, O> {
+        self.0.into_iter()
+    }
+}
+
+// sorting first gives a balanced tree, where inserting the elements one by one would give a
+// degenerate one for sorted input. Of equal elements, the first one is kept, as `insert` does.
+impl<T: Clone, O: Order<T>> FromIterator<T> for TreeSet<T, O> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> TreeSet<T, O> {
+        let mut xs: Vec<T> = iter.into_iter().collect();
+
+        xs.sort_by(O::compare);
+        xs.dedup_by(|x, y| O::compare(x, y) == Ordering::Equal);
+
+        TreeSet::from_sorted_iter(xs)
+    }
+}
+
+impl<T: Clone, O: Order<T>> Extend<T> for TreeSet<T, O> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        *self = iter.into_iter().fold(self.clone(), |t, x| t.insert(x));
+    }
+}
+
 #[test]
 fn treeset() {
-    let t: Tree<usize> = Set::empty();
+    let t: TreeSet<usize> = Set::empty();
     let t2 = t.insert(6).insert(8).insert(9).insert(7)
         .insert(4).insert(5).insert(1);
 
-    assert_eq!(t2,
+    assert_eq!(*t2.as_tree(),
             Node(
                 Rc::new(Node(
                     Rc::new(Node(
-                        Rc::new(Tip),
+                        Rc::new(Tip(PhantomData)),
                         1,
-                        Rc::new(Tip))),
+                        Rc::new(Tip(PhantomData)))),
                     4,
                     Rc::new(Node(
-                        Rc::new(Tip),
+                        Rc::new(Tip(PhantomData)),
                         5,
-                        Rc::new(Tip))))),
+                        Rc::new(Tip(PhantomData)))))),
                 6,
                 Rc::new(Node(
                     Rc::new(Node(
-                        Rc::new(Tip),
+                        Rc::new(Tip(PhantomData)),
                         7,
-                        Rc::new(Tip))),
+                        Rc::new(Tip(PhantomData)))),
                     8,
                     Rc::new(Node(
-                        Rc::new(Tip),
+                        Rc::new(Tip(PhantomData)),
                         9,
-                        Rc::new(Tip)))))));
+                        Rc::new(Tip(PhantomData))))))));
+
+    assert!(t2.member(&1));
+    assert!(t2.member(&9));
+    assert!(t2.member(&6));
+
+    assert!(!t2.member(&0));
+    assert!(!t2.member(&10));
+
+    assert_eq!(t2.member(&1), t2.member2(&1));
+    assert_eq!(t2.member(&0), t2.member2(&0));
+
+    let t3 = t2.delete(&6).delete(&1).delete(&10);
+
+    assert_eq!(*t3.as_tree(),
+            Node(
+                Rc::new(Node(
+                    Rc::new(Tip(PhantomData)),
+                    4,
+                    Rc::new(Node(
+                        Rc::new(Tip(PhantomData)),
+                        5,
+                        Rc::new(Tip(PhantomData)))))),
+                7,
+                Rc::new(Node(
+                    Rc::new(Tip(PhantomData)),
+                    8,
+                    Rc::new(Node(
+                        Rc::new(Tip(PhantomData)),
+                        9,
+                        Rc::new(Tip(PhantomData))))))));
+
+    assert!(t2.member(&6));
+    assert!(!t3.member(&6));
+    assert!(!t3.member(&1));
+    assert_eq!(t.delete(&1).as_tree(), t.as_tree());
+}
+
+#[test]
+fn treeset_iter() {
+    use order::Natural;
+
+    let t: TreeSet<usize> = vec![6, 8, 9, 7, 4, 5, 1, 6].into_iter().collect();
+
+    assert_eq!(t.iter().cloned().collect::<Vec<usize>>(), vec![1, 4, 5, 6, 7, 8, 9]);
+    assert_eq!((&t).into_iter().count(), 7);
+    assert_eq!(t.clone().into_iter().collect::<Vec<usize>>(), vec![1, 4, 5, 6, 7, 8, 9]);
+
+    let mut t2 = t.clone();
+    t2.extend(vec![3, 2, 10]);
+
+    assert_eq!(t2.into_iter().collect::<Vec<usize>>(), vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+    assert_eq!(TreeSet::<usize, Natural>::empty().iter().count(), 0);
+}
+
+#[test]
+fn treeset_order() {
+    use order::{ByKey, Projection, Reverse};
+
+    struct Lowercase;
+
+    // also projects the borrowed form of the elements, so that they can be looked up by `&str`
+    impl<S: ?Sized + AsRef<str>> Projection<S> for Lowercase {
+        type Key = String;
+
+        fn key(x: &S) -> String {
+            x.as_ref().to_lowercase()
+        }
+    }
+
+    let t: TreeSet<usize, Reverse> = vec![6, 8, 9, 7, 4, 5, 1, 6].into_iter().collect();
 
-    assert!(t2.member(1));
-    assert!(t2.member(9));
-    assert!(t2.member(6));
+    assert_eq!(t.iter().cloned().collect::<Vec<usize>>(), vec![9, 8, 7, 6, 5, 4, 1]);
+    assert!(t.member(&4));
+    assert!(t.member2(&4));
+    assert!(!t.delete(&4).member(&4));
+    assert_eq!(t.remove_min().map(|(x, _)| x), Some(9));
 
-    assert!(!t2.member(0));
-    assert!(!t2.member(10));
+    let words = vec!["b", "A", "a", "C"].into_iter().map(|w| w.to_string());
+    let u: TreeSet<String, ByKey<Lowercase>> = words.collect();
 
-    assert_eq!(t2.member(1), t2.member2(1));
-    assert_eq!(t2.member(0), t2.member2(0));
+    assert_eq!(u.iter().cloned().collect::<Vec<String>>(), vec!["A", "b", "C"]);
+    assert!(u.member("B"));
+    assert!(!u.member("d"));
+}
+
+#[test]
+fn treeset_algebra() {
+    let evens: TreeSet<usize> = (0..50).map(|x| (x * 7919) % 50 * 2).collect();
+    let threes: TreeSet<usize> = (0..34).map(|x| (x * 7919) % 34 * 3).collect();
+    let e: TreeSet<usize> = Set::empty();
+
+    let elems = |t: &TreeSet<usize>| t.iter().cloned().collect::<Vec<usize>>();
+
+    assert_eq!(elems(&evens.union(&threes)), (0..100).filter(|x| x % 2 == 0 || x % 3 == 0).collect::<Vec<usize>>());
+    assert_eq!(elems(&evens.intersection(&threes)), (0..100).filter(|x| x % 6 == 0).collect::<Vec<usize>>());
+    assert_eq!(elems(&evens.difference(&threes)), (0..100).filter(|x| x % 2 == 0 && x % 3 != 0).collect::<Vec<usize>>());
+    assert_eq!(elems(&threes.difference(&evens)), (0..100).filter(|x| x % 3 == 0 && x % 2 != 0).collect::<Vec<usize>>());
+
+    assert_eq!(evens.union(&e).as_tree(), evens.as_tree());
+    assert_eq!(e.union(&evens).as_tree(), evens.as_tree());
+    assert_eq!(evens.intersection(&e).as_tree(), e.as_tree());
+    assert_eq!(evens.difference(&e).as_tree(), evens.as_tree());
+    assert_eq!(e.difference(&evens).as_tree(), e.as_tree());
+
+    assert!(evens.intersection(&threes).is_subset(&evens));
+    assert!(evens.intersection(&threes).is_subset(&threes));
+    assert!(!evens.is_subset(&threes));
+    assert!(e.is_subset(&evens));
+    assert!(!evens.is_subset(&e));
+
+    assert!(evens.difference(&threes).is_disjoint(&threes));
+    assert!(!evens.is_disjoint(&threes));
+    assert!(e.is_disjoint(&evens));
+    assert!(!evens.is_disjoint(&evens));
+
+    // versions derived from the same tree share most of their subtrees
+    let bigger = evens.insert(1).insert(99);
+    let smaller = evens.delete(&40);
+
+    assert!(smaller.is_subset(&bigger));
+    assert!(!bigger.is_subset(&smaller));
+    assert_eq!(elems(&bigger.difference(&smaller)), vec![1, 40, 99]);
+    assert_eq!(elems(&bigger.intersection(&smaller)), elems(&smaller));
+    assert_eq!(elems(&smaller.union(&bigger)), elems(&bigger));
+    assert!(evens.same(&evens.clone()));
+    assert!(evens.is_subset(&evens.clone()));
+}
+
+#[test]
+fn treeset_navigation() {
+    use order::Reverse;
+
+    let t: &TreeSet<usize> = &vec![40, 20, 60, 10, 30, 50, 70].into_iter().collect();
+    let e: &TreeSet<usize> = &Set::empty();
+
+    assert_eq!(t.min(), Some(&10));
+    assert_eq!(t.max(), Some(&70));
+    assert_eq!(e.min(), None);
+
+    assert_eq!(t.lookup_lt(&30), Some(&20));
+    assert_eq!(t.lookup_le(&30), Some(&30));
+    assert_eq!(t.lookup_le(&35), Some(&30));
+    assert_eq!(t.lookup_gt(&30), Some(&40));
+    assert_eq!(t.lookup_ge(&30), Some(&30));
+    assert_eq!(t.lookup_ge(&31), Some(&40));
+    assert_eq!(t.lookup_lt(&10), None);
+    assert_eq!(t.lookup_gt(&70), None);
+    assert_eq!(e.lookup_le(&10), None);
+
+    assert_eq!(t.range(20..60).cloned().collect::<Vec<usize>>(), vec![20, 30, 40, 50]);
+    assert_eq!(t.range(21..61).cloned().collect::<Vec<usize>>(), vec![30, 40, 50, 60]);
+    assert_eq!(t.range(0..100).count(), 7);
+    assert_eq!(t.range(31..40).count(), 0);
+    assert_eq!(t.range(ops::Range { start: 60, end: 20 }).count(), 0);
+
+    let u: TreeSet<usize> = (0..1000).map(|x| (x * 7919) % 1000).collect();
+
+    assert!((0..1000).all(|x| u.lookup_lt(&x) == x.checked_sub(1).as_ref()));
+    assert!((0..999).all(|x| u.range(x..x + 2).cloned().collect::<Vec<usize>>() == vec![x, x + 1]));
+
+    let r: &TreeSet<usize, Reverse> = &vec![40, 20, 60].into_iter().collect();
+
+    assert_eq!(r.min(), Some(&60));
+    assert_eq!(r.lookup_lt(&40), Some(&60));
+    // 60 comes before 20 in reverse order, so the range is not empty
+    assert_eq!(r.range(ops::Range { start: 60, end: 20 }).cloned().collect::<Vec<usize>>(), vec![60, 40]);
+
+    // strings are searched for by `&str`, without building a `String`
+    let s: &TreeSet<String> = &vec!["a", "c", "e"].into_iter().map(String::from).collect();
+
+    assert_eq!(s.lookup_ge("b"), Some(&"c".to_string()));
+    assert_eq!(s.lookup_lt("b"), Some(&"a".to_string()));
+    assert_eq!(s.lookup_le("e"), Some(&"e".to_string()));
+    assert_eq!(s.lookup_gt("e"), None);
+    assert_eq!(s.range("b".to_string().."f".to_string()).count(), 2);
+}
+
+#[test]
+fn treeset_eq() {
+    use std::collections::HashSet;
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash<T: Hash>(x: &T) -> u64 {
+        let mut h = DefaultHasher::new();
+        x.hash(&mut h);
+        h.finish()
+    }
+
+    let e: TreeSet<usize> = Set::empty();
+    let t = e.insert(1).insert(2).insert(3);
+    let u = e.insert(3).insert(2).insert(1);
+
+    assert!(t.as_tree() != u.as_tree());
+    assert_eq!(t, u);
+    assert_eq!(hash(&t), hash(&u));
+    assert!(t != t.insert(4));
+    assert!(e < t);
+    assert!(t < t.insert(4));
+    assert!(t.insert(4) < t.delete(&1));
+
+    // sets of sets, told apart by their elements alone
+    let ss: TreeSet<TreeSet<usize>> = vec![t.clone(), e.clone(), u.clone(), t.delete(&2)].into_iter().collect();
+
+    assert_eq!(ss.iter().cloned().collect::<Vec<TreeSet<usize>>>(), vec![e.clone(), t.clone(), t.delete(&2)]);
+
+    let hs: HashSet<TreeSet<usize>> = vec![t.clone(), u.clone(), e.clone()].into_iter().collect();
+
+    assert_eq!(hs.len(), 2);
+    assert!(hs.contains(&e.insert(2).insert(1).insert(3)));
+}
+
+#[test]
+fn treeset_bulk() {
+    fn depth<T, O>(t: &Tree<T, O>) -> usize {
+        match *t {
+            Tip(_) => 0,
+            Node(ref l, _, ref r) => 1 + ::std::cmp::max(depth(l), depth(r))
+        }
+    }
+
+    fn size<T, O>(t: &Tree<T, O>) -> usize {
+        match *t {
+            Tip(_) => 0,
+            Node(ref l, _, ref r) => size(l) + 1 + size(r)
+        }
+    }
+
+    let t: Tree<usize> = Tree::from_sorted_iter(0..1000000);
+
+    assert_eq!(depth(&t), 20);
+    assert_eq!(t.iter().count(), 1000000);
+    assert!(t.iter().zip(0..).all(|(&x, i)| x == i));
+
+    let u: TreeSet<usize> = vec![5, 3, 9, 3, 1, 7, 5].into_iter().collect();
+
+    assert_eq!(depth(u.as_tree()), 3);
+    assert_eq!(u.iter().cloned().collect::<Vec<usize>>(), vec![1, 3, 5, 7, 9]);
+
+    let c: Tree<usize> = Tree::complete(1, 20);
+
+    assert_eq!(depth(&c), 20);
+    assert_eq!(c.iter().count(), (1 << 20) - 1);
+
+    if let Node(ref l, _, ref r) = c {
+        assert!(Rc::ptr_eq(l, r));
+    }
+
+    for m in 0..100 {
+        let b: Tree<usize> = Tree::balanced(1, m);
+
+        assert_eq!(size(&b), m);
+        assert!(m == 0 || depth(&b) == 64 - (m as u64).leading_zeros() as usize);
+    }
 }
@@ -1,95 +1,189 @@
 use std::fmt::{Display, Error, Formatter};
+use std::iter::FromIterator;
+use std::ops::Deref;
 use std::rc::Rc;
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IndexOutOfBounds;
+
 pub trait Stack<T> {
     fn empty() -> Self;
     fn is_empty(&self) -> bool;
 
     fn cons(&self, T) -> Self;
-    fn head(&self) -> T;
-    fn tail(&self) -> Self;
+    fn head(&self) -> Option<T>;
+    fn tail(&self) -> Option<Self> where Self: Sized;
 
-    fn append(&self, y: &Self) -> Self where Self: Clone + Sized {
-        if self.is_empty() {
-            y.clone()
-        } else {
-            self.tail().append(y).cons(self.head())
+    fn uncons(&self) -> Option<(T, Self)> where Self: Sized {
+        match (self.head(), self.tail()) {
+            (Some(h), Some(t)) => Some((h, t)),
+            _ => None
         }
     }
 
-    fn update(&self, i: usize, x: T) -> Self where Self: Sized {
-        if self.is_empty() {
-            panic!("index out of bounds");
+    fn append(&self, y: &Self) -> Self where Self: Clone + Sized {
+        match self.uncons() {
+            None => y.clone(),
+            Some((h, t)) => t.append(y).cons(h)
         }
+    }
 
-        if i == 0 {
-            self.tail().cons(x)
-        } else {
-            self.tail().update(i - 1, x).cons(self.head())
+    fn update(&self, i: usize, x: T) -> Result<Self, IndexOutOfBounds> where Self: Sized {
+        match self.uncons() {
+            None => Err(IndexOutOfBounds),
+            Some((_, t)) if i == 0 => Ok(t.cons(x)),
+            Some((h, t)) => t.update(i - 1, x).map(|t| t.cons(h))
         }
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum List<T> {
+#[derive(Debug, Eq, PartialEq)]
+pub enum ListNode<T> {
     Nil,
-    Cons(T, Rc<List<T>>)
+    Cons(T, List<T>)
+}
+
+// a list is a shared pointer to its spine, so that consing onto a list or taking its tail never
+// copies any cells and every version of the list shares the cells it has in common
+#[derive(Debug, Eq, PartialEq)]
+pub struct List<T>(Rc<ListNode<T>>);
+
+use stack::ListNode::{Cons, Nil};
+
+impl<T> Clone for List<T> {
+    fn clone(&self) -> List<T> {
+        List(self.0.clone())
+    }
 }
 
-use stack::List::{Cons, Nil};
+impl<T> Deref for List<T> {
+    type Target = ListNode<T>;
+
+    fn deref(&self) -> &ListNode<T> {
+        &*self.0
+    }
+}
 
 impl<T: Clone> Stack<T> for List<T> {
     fn empty() -> List<T> {
-        Nil
+        List(Rc::new(Nil))
     }
 
     fn is_empty(&self) -> bool {
-        match *self {
+        match **self {
             Nil => true,
             _ => false
         }
     }
 
     fn cons(&self, x: T) -> List<T> {
-        match *self {
-            Cons(ref h, ref t) => Cons(x, Rc::new(Cons(h.clone(), t.clone()))),
-            Nil => Cons(x, Rc::new(Nil))
+        List(Rc::new(Cons(x, self.clone())))
+    }
+
+    fn head(&self) -> Option<T> {
+        match **self {
+            Cons(ref h, _) => Some(h.clone()),
+            Nil => None
         }
     }
 
-    fn head(&self) -> T {
-        match *self {
-            Cons(ref h, _) => h.clone(),
-            Nil => panic!("head of empty list")
+    fn tail(&self) -> Option<List<T>> {
+        match **self {
+            Cons(_, ref t) => Some(t.clone()),
+            Nil => None
         }
     }
+}
+
+impl<T> List<T> {
+    pub fn iter<'a>(&'a self) -> Iter<'a, T> {
+        Iter { next: self }
+    }
+}
+
+impl<T: Clone> List<T> {
+    pub fn reverse(&self) -> List<T> {
+        self.iter().fold(Stack::empty(), |l: List<T>, x| l.cons(x.clone()))
+    }
+}
+
+pub struct Iter<'a, T: 'a> {
+    next: &'a List<T>
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        match **self.next {
+            Cons(ref h, ref t) => {
+                self.next = t;
+                Some(h)
+            },
+            Nil => None
+        }
+    }
+}
+
+pub struct IntoIter<T> {
+    next: List<T>
+}
+
+impl<T: Clone> Iterator for IntoIter<T> {
+    type Item = T;
 
-    fn tail(&self) -> List<T> {
-        match *self {
-            Nil => panic!("tail of empty list"),
-            Cons(_, ref t) => (**t).clone()
+    fn next(&mut self) -> Option<T> {
+        match self.next.uncons() {
+            Some((h, t)) => {
+                self.next = t;
+                Some(h)
+            },
+            None => None
         }
     }
 }
 
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T: Clone> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { next: self }
+    }
+}
+
+impl<T: Clone> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> List<T> {
+        let xs: Vec<T> = iter.into_iter().collect();
+        xs.into_iter().rev().fold(Stack::empty(), |l: List<T>, x| l.cons(x))
+    }
+}
+
+impl<T: Clone> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let l: List<T> = iter.into_iter().collect();
+        *self = self.append(&l);
+    }
+}
+
 impl<T: Display> Display for List<T> {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         try!(write!(f, "["));
 
-        fn _loop<T: Display>(f: &mut Formatter, l: &List<T>, first: bool) -> Result<(), Error> {
-            match *l {
-                Cons(ref h, ref t) => {
-                    if first { try!(write!(f, "{}", h)); }
-                    else { try!(write!(f, ", {}", h)); }
-
-                    _loop(f, t, false)
-                },
-                Nil => Result::Ok(())
-            }
+        for (i, x) in self.iter().enumerate() {
+            if i == 0 { try!(write!(f, "{}", x)); }
+            else { try!(write!(f, ", {}", x)); }
         }
 
-        try!(_loop(f, self, true));
-
         write!(f, "]")
     }
 }
@@ -102,22 +196,57 @@ fn list() {
 
     assert!(l1.is_empty());
     assert!(!l2.is_empty());
-    assert_eq!(l2, Cons(1, Rc::new(Cons(2, Rc::new(Cons(3, Rc::new(Nil)))))));
-    assert_eq!(l2.head(), 1);
-    assert_eq!(l2.tail(), Cons(2, Rc::new(Cons(3, Rc::new(Nil)))));
+    assert_eq!(l2.head(), Some(1));
+    assert_eq!(l2.tail(), Some(l1.cons(3).cons(2)));
+    assert_eq!(l2.uncons(), Some((1, l1.cons(3).cons(2))));
+
+    assert_eq!(l1.head(), None);
+    assert_eq!(l1.tail(), None);
+    assert_eq!(l1.uncons(), None);
 
     assert_eq!(l1.append(&l2), l2);
     assert_eq!(l2.append(&l1), l2);
 
-    assert_eq!(l2.append(&l3),
-               Cons(1, Rc::new(Cons(2, Rc::new(Cons(3, Rc::new(Cons(4, Rc::new(Cons(5, Rc::new(Nil)))))))))));
+    assert_eq!(l2.append(&l3), l1.cons(5).cons(4).cons(3).cons(2).cons(1));
+    assert_eq!(l3.append(&l2), l1.cons(3).cons(2).cons(1).cons(5).cons(4));
+
+    assert_eq!(l3.update(0, 0), Ok(l1.cons(5).cons(0)));
+    assert_eq!(l3.update(1, 0), Ok(l1.cons(0).cons(4)));
+
+    assert_eq!(l3.update(2, 0), Err(IndexOutOfBounds));
+    assert_eq!(l1.update(0, 0), Err(IndexOutOfBounds));
+
+    // both versions share the original spine
+    let l4 = l2.cons(0);
+    let l5 = l2.cons(1);
+
+    assert!(Rc::ptr_eq(&l4.tail().unwrap().0, &l2.0));
+    assert!(Rc::ptr_eq(&l5.tail().unwrap().0, &l2.0));
+
+    // the appended list is shared as the tail of the result
+    let l6 = l2.append(&l3).tail().unwrap().tail().unwrap().tail().unwrap();
+
+    assert!(Rc::ptr_eq(&l6.0, &l3.0));
+}
+
+#[test]
+fn list_iter() {
+    let l1: List<usize> = Stack::empty();
+    let l2 = l1.cons(3).cons(2).cons(1);
+    let l3 = l1.cons(5).cons(4);
+
+    assert_eq!(l2.iter().cloned().collect::<Vec<usize>>(), vec![1, 2, 3]);
+    assert_eq!(l1.iter().count(), 0);
+    assert_eq!(l2.clone().into_iter().collect::<Vec<usize>>(), vec![1, 2, 3]);
+    assert_eq!((&l3).into_iter().map(|x| x * 2).collect::<Vec<usize>>(), vec![8, 10]);
+    assert_eq!(vec![1, 2, 3].into_iter().collect::<List<usize>>(), l2);
 
-    assert_eq!(l3.append(&l2),
-               Cons(4, Rc::new(Cons(5, Rc::new(Cons(1, Rc::new(Cons(2, Rc::new(Cons(3, Rc::new(Nil)))))))))));
+    let mut l7 = l2.clone();
+    l7.extend(vec![4, 5]);
 
-    assert_eq!(l3.update(0, 0),
-               Cons(0, Rc::new(Cons(5, Rc::new(Nil)))));
+    assert_eq!(l7, l2.append(&l3));
+    assert_eq!(format!("{}", l7), "[1, 2, 3, 4, 5]");
 
-    assert_eq!(l3.update(1, 0),
-               Cons(4, Rc::new(Cons(0, Rc::new(Nil)))));
+    assert_eq!(l7.reverse(), l1.cons(1).cons(2).cons(3).cons(4).cons(5));
+    assert_eq!(l1.reverse(), l1);
 }
@@ -0,0 +1,331 @@
+use std::iter::FromIterator;
+use std::rc::Rc;
+
+use susp::Susp;
+
+// Chapter 4:
+// streams are lazy lists with memoized cells. Incremental functions only do a constant amount of
+// work for every cell of the result that is forced, while monolithic functions do all of their
+// work as soon as the first cell of the result is forced.
+
+#[derive(Clone)]
+pub enum StreamCell<T> {
+    Nil,
+    Cons(T, Stream<T>)
+}
+
+use stream::StreamCell::{Cons, Nil};
+
+/// A lazy list whose cells are suspensions, shared between all the streams built from it. Every
+/// cell is evaluated at most once no matter how many versions of the stream force it.
+pub struct Stream<T>(Rc<Susp<StreamCell<T>>>);
+
+impl<T> Clone for Stream<T> {
+    fn clone(&self) -> Stream<T> {
+        Stream(self.0.clone())
+    }
+}
+
+impl<T: Clone + 'static> Stream<T> {
+    pub fn empty() -> Stream<T> {
+        Stream(Rc::new(Susp::ready(Nil)))
+    }
+
+    /// A stream whose first cell is only computed when the stream is forced.
+    pub fn lazy<F: FnOnce() -> StreamCell<T> + 'static>(f: F) -> Stream<T> {
+        Stream(Rc::new(Susp::new(f)))
+    }
+
+    pub fn force(&self) -> &StreamCell<T> {
+        self.0.force()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match *self.force() {
+            Nil => true,
+            _ => false
+        }
+    }
+
+    pub fn cons(&self, x: T) -> Stream<T> {
+        Stream(Rc::new(Susp::ready(Cons(x, self.clone()))))
+    }
+
+    pub fn head(&self) -> Option<T> {
+        match *self.force() {
+            Cons(ref x, _) => Some(x.clone()),
+            Nil => None
+        }
+    }
+
+    pub fn tail(&self) -> Option<Stream<T>> {
+        match *self.force() {
+            Cons(_, ref s) => Some(s.clone()),
+            Nil => None
+        }
+    }
+
+    /// Incremental: each cell of the result is only computed when it is forced.
+    pub fn append(&self, t: &Stream<T>) -> Stream<T> {
+        let s = self.clone();
+        let t = t.clone();
+
+        Stream::lazy(move || {
+            match *s.force() {
+                Cons(ref x, ref s1) => Cons(x.clone(), s1.append(&t)),
+                Nil => t.force().clone()
+            }
+        })
+    }
+
+    /// Incremental: each cell of the result is only computed when it is forced.
+    pub fn take(&self, n: usize) -> Stream<T> {
+        let s = self.clone();
+
+        Stream::lazy(move || {
+            if n == 0 {
+                return Nil;
+            }
+
+            match *s.force() {
+                Cons(ref x, ref s1) => Cons(x.clone(), s1.take(n - 1)),
+                Nil => Nil
+            }
+        })
+    }
+
+    /// Monolithic: forcing the first cell of the result forces the first `n` cells of the stream.
+    pub fn drop(&self, n: usize) -> Stream<T> {
+        let s = self.clone();
+
+        Stream::lazy(move || {
+            let mut s = s;
+
+            for _ in 0..n {
+                let next = match *s.force() {
+                    Cons(_, ref s1) => s1.clone(),
+                    Nil => return Nil
+                };
+
+                s = next;
+            }
+
+            let c = s.force().clone();
+            c
+        })
+    }
+
+    /// A stream that pulls its elements from the iterator only as its cells are forced, so the
+    /// iterator may well be infinite.
+    pub fn generate<I: Iterator<Item = T> + 'static>(iter: I) -> Stream<T> {
+        let mut iter = iter;
+
+        Stream::lazy(move || {
+            match iter.next() {
+                Some(x) => Cons(x, Stream::generate(iter)),
+                None => Nil
+            }
+        })
+    }
+
+    /// Incremental: each cell of the result is only computed when it is forced.
+    pub fn map<U: Clone + 'static, F: Fn(&T) -> U + 'static>(&self, f: F) -> Stream<U> {
+        fn aux<T: Clone + 'static, U: Clone + 'static, F: Fn(&T) -> U + 'static>(s: Stream<T>, f: Rc<F>) -> Stream<U> {
+            Stream::lazy(move || {
+                match *s.force() {
+                    Cons(ref x, ref s1) => Cons(f(x), aux(s1.clone(), f.clone())),
+                    Nil => Nil
+                }
+            })
+        }
+
+        aux(self.clone(), Rc::new(f))
+    }
+
+    /// Incremental: forcing a cell of the result only forces the cells of the stream up to the
+    /// next element that satisfies the predicate.
+    pub fn filter<P: Fn(&T) -> bool + 'static>(&self, p: P) -> Stream<T> {
+        fn aux<T: Clone + 'static, P: Fn(&T) -> bool + 'static>(s: Stream<T>, p: Rc<P>) -> Stream<T> {
+            Stream::lazy(move || {
+                let mut s = s;
+
+                loop {
+                    let next = match *s.force() {
+                        Cons(ref x, ref s1) if p(x) => return Cons(x.clone(), aux(s1.clone(), p.clone())),
+                        Cons(_, ref s1) => s1.clone(),
+                        Nil => return Nil
+                    };
+
+                    s = next;
+                }
+            })
+        }
+
+        aux(self.clone(), Rc::new(p))
+    }
+
+    /// Monolithic: forcing the first cell of the result forces the whole stream.
+    pub fn reverse(&self) -> Stream<T> {
+        let s = self.clone();
+
+        Stream::lazy(move || {
+            let mut r = Nil;
+            let mut s = s;
+
+            loop {
+                let next = match *s.force() {
+                    Cons(ref x, ref s1) => {
+                        r = Cons(x.clone(), Stream(Rc::new(Susp::ready(r))));
+                        s1.clone()
+                    },
+                    Nil => return r
+                };
+
+                s = next;
+            }
+        })
+    }
+}
+
+impl<T> Stream<T> {
+    /// Iterates over the elements of the stream, forcing its cells along the way.
+    pub fn iter<'a>(&'a self) -> Iter<'a, T> {
+        Iter { next: self }
+    }
+}
+
+pub struct Iter<'a, T: 'a> {
+    next: &'a Stream<T>
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        match *self.next.0.force() {
+            Cons(ref x, ref s) => {
+                self.next = s;
+                Some(x)
+            },
+            Nil => None
+        }
+    }
+}
+
+pub struct IntoIter<T> {
+    next: Stream<T>
+}
+
+impl<T: Clone + 'static> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let (x, s) = match *self.next.force() {
+            Cons(ref x, ref s) => (x.clone(), s.clone()),
+            Nil => return None
+        };
+
+        self.next = s;
+        Some(x)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Stream<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T: Clone + 'static> IntoIterator for Stream<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { next: self }
+    }
+}
+
+impl<T: Clone + 'static> FromIterator<T> for Stream<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Stream<T> {
+        let xs: Vec<T> = iter.into_iter().collect();
+        Stream::generate(xs.into_iter())
+    }
+}
+
+#[test]
+fn stream() {
+    use std::cell::Cell;
+
+    let s: Stream<usize> = Stream::empty();
+    let s1 = s.cons(3).cons(2).cons(1);
+    let s2 = s.cons(5).cons(4);
+
+    assert!(s.is_empty());
+    assert_eq!(s1.head(), Some(1));
+    assert_eq!(s1.tail().and_then(|s| s.head()), Some(2));
+    assert_eq!(s.head(), None);
+
+    let s3 = s1.append(&s2);
+    let s4 = s3.reverse();
+
+    let mut xs = vec![];
+    let mut t = s3.clone();
+
+    while let Some(x) = t.head() {
+        xs.push(x);
+        t = t.tail().unwrap();
+    }
+
+    assert_eq!(xs, vec![1, 2, 3, 4, 5]);
+    assert_eq!(s4.head(), Some(5));
+
+    assert_eq!(s3.take(2).tail().and_then(|s| s.head()), Some(2));
+    assert!(s3.take(2).tail().and_then(|s| s.tail()).unwrap().is_empty());
+    assert!(s3.take(0).is_empty());
+    assert_eq!(s3.drop(3).head(), Some(4));
+    assert!(s3.drop(5).is_empty());
+    assert!(s3.drop(10).is_empty());
+
+    // appending does not force anything until the result is forced
+    let forced = Rc::new(Cell::new(false));
+    let f = forced.clone();
+    let s5: Stream<usize> = Stream::lazy(move || { f.set(true); Nil });
+    let s6 = s5.append(&s1);
+
+    assert!(!forced.get());
+    assert_eq!(s6.head(), Some(1));
+    assert!(forced.get());
+}
+
+#[test]
+fn stream_lazy() {
+    use std::cell::Cell;
+
+    let pulled = Rc::new(Cell::new(0));
+    let p = pulled.clone();
+
+    // an infinite stream of naturals, of which only the forced prefix is ever computed
+    let nats: Stream<usize> = Stream::generate((0..).inspect(move |_| p.set(p.get() + 1)));
+    let evens = nats.filter(|x| x % 2 == 0);
+    let squares = evens.map(|x| x * x);
+
+    assert_eq!(pulled.get(), 0);
+    assert_eq!(squares.take(4).iter().cloned().collect::<Vec<usize>>(), vec![0, 4, 16, 36]);
+    assert_eq!(pulled.get(), 7);
+
+    // the cells are shared, so walking the stream again does not recompute them
+    assert_eq!(nats.take(7).into_iter().collect::<Vec<usize>>(), vec![0, 1, 2, 3, 4, 5, 6]);
+    assert_eq!(pulled.get(), 7);
+
+    assert_eq!(nats.drop(100).head(), Some(100));
+    assert_eq!(pulled.get(), 101);
+
+    let s: Stream<usize> = vec![1, 2, 3].into_iter().collect();
+
+    assert_eq!(s.reverse().iter().cloned().collect::<Vec<usize>>(), vec![3, 2, 1]);
+    assert_eq!(s.append(&s).map(|x| x + 1).into_iter().collect::<Vec<usize>>(), vec![2, 3, 4, 2, 3, 4]);
+    assert_eq!((&s).into_iter().count(), 3);
+}
@@ -0,0 +1,47 @@
+use std::cell::{Cell, OnceCell};
+
+/// A suspended computation that is evaluated at most once, the first time it is forced, with
+/// its result memoized for every later force.
+pub struct Susp<T> {
+    thunk: Cell<Option<Box<dyn FnOnce() -> T>>>,
+    value: OnceCell<T>
+}
+
+impl<T> Susp<T> {
+    pub fn new<F: FnOnce() -> T + 'static>(f: F) -> Susp<T> {
+        Susp { thunk: Cell::new(Some(Box::new(f))), value: OnceCell::new() }
+    }
+
+    /// A suspension whose value is already known.
+    pub fn ready(x: T) -> Susp<T> {
+        let value = OnceCell::new();
+        let _ = value.set(x);
+        Susp { thunk: Cell::new(None), value: value }
+    }
+
+    pub fn force(&self) -> &T {
+        self.value.get_or_init(|| {
+            match self.thunk.take() {
+                Some(f) => f(),
+                None => panic!("suspension forced while being evaluated")
+            }
+        })
+    }
+}
+
+#[test]
+fn susp() {
+    use std::rc::Rc;
+
+    let count = Rc::new(Cell::new(0));
+    let c = count.clone();
+
+    let s = Susp::new(move || { c.set(c.get() + 1); 42 });
+
+    assert_eq!(count.get(), 0);
+    assert_eq!(*s.force(), 42);
+    assert_eq!(*s.force(), 42);
+    assert_eq!(count.get(), 1);
+
+    assert_eq!(*Susp::ready(1).force(), 1);
+}
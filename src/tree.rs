@@ -1,19 +1,35 @@
-use std::cmp::max;
+use std::borrow::Borrow;
+use std::cmp::{max, Ordering};
 use std::fmt::{Display, Error, Formatter};
+use std::marker::PhantomData;
 use std::num::Int;
+use std::ops;
 use std::rc::Rc;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum Tree<T> {
-    Tip,
-    Node(Rc<Tree<T>>, T, Rc<Tree<T>>),
+use order::{Natural, Order};
+
+// the ordering only matters to the trees used as sets and maps, which keep their elements sorted
+// by it. Tips carry it since a type parameter that is only used recursively is rejected.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Tree<T, O = Natural> {
+    Tip(PhantomData<O>),
+    Node(Rc<Tree<T, O>>, T, Rc<Tree<T, O>>),
 }
 
 use tree::Tree::{Node, Tip};
 
-impl<T: Display> Display for Tree<T> {
+impl<T: Clone, O> Clone for Tree<T, O> {
+    fn clone(&self) -> Tree<T, O> {
+        match *self {
+            Tip(_) => Tip(PhantomData),
+            Node(ref l, ref x, ref r) => Node(l.clone(), x.clone(), r.clone())
+        }
+    }
+}
+
+impl<T: Display, O> Display for Tree<T, O> {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        fn aux<T: Display>(f: &mut Formatter, t: &Tree<T>, right: bool, indent: &str) -> Result<(), Error> {
+        fn aux<T: Display, O>(f: &mut Formatter, t: &Tree<T, O>, right: bool, indent: &str) -> Result<(), Error> {
             match *t {
                 Node(ref l, ref x, ref r) => {
                     try!(aux(f, r, true, &(indent.to_string() + if right { "        " } else { " |      " })));
@@ -26,7 +42,7 @@ impl<T: Display> Display for Tree<T> {
 
                     aux(f, l, false, &(indent.to_string() + if right { " |      " } else { "        " }))
                 },
-                Tip => {
+                Tip(_) => {
                     try!(write!(f, "{}", indent));
                     try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
                     try!(write!(f, "{}", "----- "));
@@ -42,23 +58,93 @@ impl<T: Display> Display for Tree<T> {
                 try!(writeln!(f, "({})", x));
                 aux(f, l, false, "")
             },
-            Tip => Result::Ok(())
+            Tip(_) => Result::Ok(())
+        }
+    }
+}
+
+impl<T: Clone, O> Tree<T, O> {
+    /// Removes the smallest element of the tree, returning it along with the remaining tree.
+    pub fn remove_min(&self) -> Option<(T, Tree<T, O>)> {
+        match *self {
+            Tip(_) => None,
+            Node(ref l, ref x, ref r) =>
+                match l.remove_min() {
+                    None => Some((x.clone(), (**r).clone())),
+                    Some((m, l1)) => Some((m, Node(Rc::new(l1), x.clone(), r.clone())))
+                }
+        }
+    }
+}
+
+impl<T: Clone, O> Tree<T, O> {
+    // Exercise 2.5 (a):
+    // O(d) time, since both subtrees of every node are the same tree
+    pub fn complete(x: T, d: usize) -> Tree<T, O> {
+        (0..d).fold(Tip(PhantomData), |t, _| {
+            let t = Rc::new(t);
+            Node(t.clone(), x.clone(), t)
+        })
+    }
+
+    // Exercise 2.5 (b):
+    // O(log m) time, building the trees of size m and m + 1 together so that every size only
+    // needs to be built once
+    pub fn balanced(x: T, m: usize) -> Tree<T, O> {
+        // the trees of size m and m + 1
+        type Pair<T, O> = (Rc<Tree<T, O>>, Rc<Tree<T, O>>);
+
+        fn create2<T: Clone, O>(x: &T, m: usize) -> Pair<T, O> {
+            if m == 0 {
+                (Rc::new(Tip(PhantomData)), Rc::new(Node(Rc::new(Tip(PhantomData)), x.clone(), Rc::new(Tip(PhantomData)))))
+            } else {
+                let (s, t) = create2(x, (m - 1) / 2);
+
+                if m % 2 == 1 {
+                    (Rc::new(Node(s.clone(), x.clone(), s.clone())), Rc::new(Node(s, x.clone(), t)))
+                } else {
+                    (Rc::new(Node(s, x.clone(), t.clone())), Rc::new(Node(t.clone(), x.clone(), t)))
+                }
+            }
         }
+
+        (*create2(&x, m).0).clone()
     }
 }
 
-impl<T: Ord> Tree<T> {
+impl<T, O> Tree<T, O> {
+    /// Builds a perfectly balanced tree out of elements given in order and without duplicates,
+    /// in O(n) time.
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> Tree<T, O> {
+        fn build<T, O, I: Iterator<Item = T>>(n: usize, it: &mut I) -> Tree<T, O> {
+            if n == 0 {
+                return Tip(PhantomData);
+            }
+
+            let l = build(n / 2, it);
+            let x = it.next().unwrap();
+            let r = build(n - n / 2 - 1, it);
+
+            Node(Rc::new(l), x, Rc::new(r))
+        }
+
+        let xs: Vec<T> = iter.into_iter().collect();
+        build(xs.len(), &mut xs.into_iter())
+    }
+}
+
+impl<T, O> Tree<T, O> {
     // Exercise 2.2:
     // only performs at most d + 1 comparisons, where d is the depth of the tree
-    pub fn member2(&self, x: T) -> bool {
+    pub fn member2<Q: ?Sized>(&self, x: &Q) -> bool where T: Borrow<Q>, O: Order<Q> {
         match *self {
-            Tip => false,
+            Tip(_) => false,
             Node(_, ref v, _) => {
-                fn member_aux<T: Ord>(t: &Tree<T>, x: T, c: &T) -> bool {
+                fn member_aux<T: Borrow<Q>, Q: ?Sized, O: Order<Q>>(t: &Tree<T, O>, x: &Q, c: &T) -> bool {
                     match *t {
-                        Tip => x == *c,
+                        Tip(_) => O::compare(x, c.borrow()) == Ordering::Equal,
                         Node(ref l, ref v, ref r) =>
-                            if x < *v {
+                            if O::lt(x, v.borrow()) {
                                 member_aux(l, x, c)
                             } else {
                                 member_aux(r, x, v)
@@ -71,3 +157,210 @@ impl<T: Ord> Tree<T> {
         }
     }
 }
+
+// The ordered queries take any borrowed form `Q` of the elements, as the lookups of maps do.
+impl<T, O> Tree<T, O> {
+    /// The smallest element of the tree.
+    pub fn min(&self) -> Option<&T> {
+        let mut t = self;
+        let mut min = None;
+
+        while let Node(ref l, ref x, _) = *t {
+            min = Some(x);
+            t = l;
+        }
+
+        min
+    }
+
+    /// The biggest element of the tree.
+    pub fn max(&self) -> Option<&T> {
+        let mut t = self;
+        let mut max = None;
+
+        while let Node(_, ref x, ref r) = *t {
+            max = Some(x);
+            t = r;
+        }
+
+        max
+    }
+
+    /// The biggest element smaller than `q`.
+    pub fn lookup_lt<Q: ?Sized>(&self, q: &Q) -> Option<&T> where T: Borrow<Q>, O: Order<Q> {
+        self.nearest(q, true, |c| c == Ordering::Less)
+    }
+
+    /// The biggest element smaller than or equal to `q`.
+    pub fn lookup_le<Q: ?Sized>(&self, q: &Q) -> Option<&T> where T: Borrow<Q>, O: Order<Q> {
+        self.nearest(q, true, |c| c != Ordering::Greater)
+    }
+
+    /// The smallest element bigger than `q`.
+    pub fn lookup_gt<Q: ?Sized>(&self, q: &Q) -> Option<&T> where T: Borrow<Q>, O: Order<Q> {
+        self.nearest(q, false, |c| c == Ordering::Greater)
+    }
+
+    /// The smallest element bigger than or equal to `q`.
+    pub fn lookup_ge<Q: ?Sized>(&self, q: &Q) -> Option<&T> where T: Borrow<Q>, O: Order<Q> {
+        self.nearest(q, false, |c| c != Ordering::Less)
+    }
+
+    // the elements for which `wanted` holds come either all below or all above the others, and
+    // the one closest to them is the last wanted element on the search path
+    fn nearest<Q: ?Sized, F: Fn(Ordering) -> bool>(&self, q: &Q, below: bool, wanted: F) -> Option<&T>
+        where T: Borrow<Q>, O: Order<Q> {
+        let mut t = self;
+        let mut nearest = None;
+
+        while let Node(ref l, ref x, ref r) = *t {
+            if wanted(O::compare(x.borrow(), q)) {
+                nearest = Some(x);
+                t = if below { r } else { l };
+            } else {
+                t = if below { l } else { r };
+            }
+        }
+
+        nearest
+    }
+
+    /// Iterates in order over the elements in `range`, i.e. the ones that are at least
+    /// `range.start` and smaller than `range.end`. Only the subtrees holding some of them are
+    /// visited, and only as far as the iterator is consumed.
+    pub fn range<'a, Q>(&'a self, range: ops::Range<Q>) -> Range<'a, T, Q, O> where T: Borrow<Q>, O: Order<Q> {
+        let mut it = Range { stack: vec![], end: range.end };
+        let mut t = self;
+
+        while let Node(ref l, ref x, ref r) = *t {
+            if O::lt(x.borrow(), &range.start) {
+                t = r;
+            } else {
+                it.stack.push(t);
+                t = l;
+            }
+        }
+
+        it
+    }
+}
+
+pub struct Range<'a, T: 'a, Q, O: 'a = Natural> {
+    stack: Vec<&'a Tree<T, O>>,
+    end: Q
+}
+
+impl<'a, T: Borrow<Q>, Q, O: Order<Q>> Iterator for Range<'a, T, Q, O> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        match self.stack.pop() {
+            Some(&Node(_, ref x, ref r)) if O::lt(x.borrow(), &self.end) => {
+                let mut t = &**r;
+
+                while let Node(ref l, _, _) = *t {
+                    self.stack.push(t);
+                    t = l;
+                }
+
+                Some(x)
+            },
+            _ => {
+                self.stack.clear();
+                None
+            }
+        }
+    }
+}
+
+impl<T, O> Tree<T, O> {
+    /// Iterates over the elements of the tree in order.
+    pub fn iter<'a>(&'a self) -> Iter<'a, T, O> {
+        let mut it = Iter { stack: vec![] };
+        it.push_left(self);
+        it
+    }
+}
+
+pub struct Iter<'a, T: 'a, O: 'a = Natural> {
+    stack: Vec<&'a Tree<T, O>>
+}
+
+impl<'a, T, O> Iter<'a, T, O> {
+    fn push_left(&mut self, mut t: &'a Tree<T, O>) {
+        while let Node(ref l, _, _) = *t {
+            self.stack.push(t);
+            t = l;
+        }
+    }
+}
+
+impl<'a, T, O> Iterator for Iter<'a, T, O> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        match self.stack.pop() {
+            Some(&Node(_, ref x, ref r)) => {
+                self.push_left(r);
+                Some(x)
+            },
+            _ => None
+        }
+    }
+}
+
+pub struct IntoIter<T, O = Natural> {
+    stack: Vec<Rc<Tree<T, O>>>
+}
+
+impl<T, O> IntoIter<T, O> {
+    fn push_left(&mut self, mut t: Rc<Tree<T, O>>) {
+        loop {
+            let l = match *t {
+                Node(ref l, _, _) => l.clone(),
+                Tip(_) => return
+            };
+
+            self.stack.push(t);
+            t = l;
+        }
+    }
+}
+
+impl<T: Clone, O> Iterator for IntoIter<T, O> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match self.stack.pop() {
+            Some(t) =>
+                match *t {
+                    Node(_, ref x, ref r) => {
+                        self.push_left(r.clone());
+                        Some(x.clone())
+                    },
+                    Tip(_) => None
+                },
+            None => None
+        }
+    }
+}
+
+impl<'a, T, O> IntoIterator for &'a Tree<T, O> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, O>;
+
+    fn into_iter(self) -> Iter<'a, T, O> {
+        self.iter()
+    }
+}
+
+impl<T: Clone, O> IntoIterator for Tree<T, O> {
+    type Item = T;
+    type IntoIter = IntoIter<T, O>;
+
+    fn into_iter(self) -> IntoIter<T, O> {
+        let mut it = IntoIter { stack: vec![] };
+        it.push_left(Rc::new(self));
+        it
+    }
+}
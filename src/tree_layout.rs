@@ -1,4 +1,5 @@
 use std::fmt::{Debug, Display};
+use std::marker::PhantomData;
 use std::num::Float;
 use std::rc::Rc;
 
@@ -30,7 +31,7 @@ pub fn pretty_print<T: Clone + Display + Debug>(t: &Tree<(T, f64)>) {
             Some(t) => {
                 let o =
                     match *t {
-                    Tip => offset,
+                    Tip(_) => offset,
                     Node(ref l, (ref v, x), ref r) => {
                         let sp = x - offset;
                         print_spaces((sp * factor) as usize);
@@ -53,7 +54,7 @@ pub fn pretty_print<T: Clone + Display + Debug>(t: &Tree<(T, f64)>) {
 pub fn move_by_leftmost<T: Clone>(t: &Tree<(T, f64)>) -> Tree<(T, f64)> {
     fn move_by_offset<T: Clone>(t: &Tree<(T, f64)>, o: f64) -> Tree<(T, f64)> {
         match *t {
-            Tip => Tip,
+            Tip(_) => Tip(PhantomData),
             Node(ref l, (ref v, x), ref r) => {
                 Node(Rc::new(move_by_offset(&*l, o)), (v.clone(), x + o), Rc::new(move_by_offset(&*r, o)))
             }
@@ -62,7 +63,7 @@ pub fn move_by_leftmost<T: Clone>(t: &Tree<(T, f64)>) -> Tree<(T, f64)> {
 
     fn find_leftmost<T>(t: &Tree<(T, f64)>, current: f64) -> f64 {
         match *t {
-            Tip => current,
+            Tip(_) => current,
             Node(ref l, (_, x), ref r) => {
                 let n = x.min(current);
                 find_leftmost(l, n).min(find_leftmost(r, n))
@@ -76,7 +77,7 @@ pub fn move_by_leftmost<T: Clone>(t: &Tree<(T, f64)>) -> Tree<(T, f64)> {
 pub fn absolute_new<T: Clone + Display>(t: &Tree<(T, f64)>) -> Tree<(T, f64)> {
     fn aux<T: Clone + Display>(t: &Tree<(T, f64)>, d: f64, vd: f64) -> Tree<(T, f64)> {
         match *t {
-            Tip => Tip,
+            Tip(_) => Tip(PhantomData),
             Node(ref l, (ref v, x), ref r) => {
                 let a = d + x;
                 let d = a + (a.signum() * vd);
@@ -93,7 +94,7 @@ pub fn absolute_new<T: Clone + Display>(t: &Tree<(T, f64)>) -> Tree<(T, f64)> {
 pub fn absolute<T: Clone + Display>(t: &Tree<(T, f64)>) -> Tree<(T, f64)> {
     fn aux<T: Clone + Display>(t: &Tree<(T, f64)>, d: f64) -> Tree<(T, f64)> {
         match *t {
-            Tip => Tip,
+            Tip(_) => Tip(PhantomData),
             Node(ref l, (ref v, x), ref r) => {
                 let a = d + x;
                 Node(Rc::new(aux(&*l, a)), (v.clone(), a), Rc::new(aux(&*r, a)))
@@ -107,7 +108,7 @@ pub fn absolute<T: Clone + Display>(t: &Tree<(T, f64)>) -> Tree<(T, f64)> {
 pub fn design<T: Clone>(t: &Tree<T>) -> Tree<(T, f64)> {
     fn aux<T: Clone>(t: &Tree<T>) -> (Tree<(T, f64)>, Extent) {
         match *t {
-            Tip => (Tip, vec![]),
+            Tip(_) => (Tip(PhantomData), vec![]),
             Node(ref l, ref v, ref r) => {
                 let (trees, extents): (Vec<Tree<(T, f64)>>, Vec<Extent>) = vec![aux(l), aux(r)].into_iter().unzip();
                 let positions = fit_list(extents.clone());
@@ -134,7 +135,7 @@ pub fn design<T: Clone>(t: &Tree<T>) -> Tree<(T, f64)> {
 
 fn move_tree<T: Clone>(t: &Tree<(T, f64)>, x1: f64) -> Tree<(T, f64)> {
     match *t {
-        Tip => Tip,
+        Tip(_) => Tip(PhantomData),
         Node(ref l, (ref v, x), ref r) => Node(l.clone(), (v.clone(), x + x1), r.clone())
     }
 }
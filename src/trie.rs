@@ -1,8 +1,14 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::fmt::{Display, Error, Formatter};
+use std::fmt::{Debug, Display, Error, Formatter};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
 use std::rc::Rc;
 
-use map::Map;
+use map::{Lookup, Map, TreeMap};
+use order::{Natural, Order};
+use weight_balanced_tree::WeightBalancedMap;
 
 #[derive(Clone, Debug)]
 pub enum PatriciaTrie<T> {
@@ -83,29 +89,177 @@ impl<T: Clone> Map<String, T> for PatriciaTrie<T> {
             }
         }
     }
+}
+
+// the keys are taken apart as strings, so any `Q` that can be viewed as one will do
+impl<T: Clone, Q: ?Sized + AsRef<str>> Lookup<T, Q> for PatriciaTrie<T> {
+    fn find(&self, k: &Q) -> Option<&T> {
+        let k = k.as_ref();
 
-    fn lookup(&self, k: String) -> T {
         match *self {
-            Tip => panic!("lookup on empty tree."),
+            Tip => None,
             Node { ref key, ref value, ref children } => {
                 if k == *key {
-                    match *value {
-                        Some(ref v) => v.clone(),
-                        None => panic!("element does not exist"),
-                    }
-                } else if k.starts_with(key) {
+                    value.as_ref()
+                } else if k.starts_with(&key[..]) {
                     match children.get(&k.char_at(key.len())) {
-                        Some(t) => t.lookup(k[key.len()..].to_string()),
-                        None => panic!("element does not exist"),
+                        Some(t) => t.find(&k[key.len()..]),
+                        None => None,
+                    }
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn remove(&self, k: &Q) -> PatriciaTrie<T> {
+        // drops nodes left without a value or children and merges valueless nodes with their
+        // only child, so that the trie stays path compressed
+        fn compress<T: Clone>(key: String, value: Option<T>, children: HashMap<char, Rc<PatriciaTrie<T>>>) -> PatriciaTrie<T> {
+            if value.is_some() || children.len() > 1 {
+                return Node { key: key, value: value, children: children };
+            }
+
+            match children.values().next() {
+                Some(c) =>
+                    match **c {
+                        Node { key: ref k1, ref value, ref children } =>
+                            Node { key: key + k1, value: value.clone(), children: children.clone() },
+                        Tip => Tip
+                    },
+                None => Tip
+            }
+        }
+
+        let k = k.as_ref();
+
+        match *self {
+            Tip => Tip,
+            Node { ref key, ref value, ref children } => {
+                if k == *key {
+                    compress(key.clone(), None, children.clone())
+                } else if k.starts_with(&key[..]) {
+                    let c = k.char_at(key.len());
+
+                    match children.get(&c) {
+                        Some(t) => {
+                            let mut children = children.clone();
+
+                            match t.remove(&k[key.len()..]) {
+                                Tip => { children.remove(&c); },
+                                t => { children.insert(c, Rc::new(t)); }
+                            }
+
+                            compress(key.clone(), value.clone(), children)
+                        },
+                        None => self.clone()
                     }
                 } else {
-                    panic!("element does not exist")
+                    self.clone()
                 }
             }
         }
     }
 }
 
+// children in descending order of their first character, so that popping them off a stack visits
+// them in lexicographic order
+fn sorted_children<T>(children: &HashMap<char, Rc<PatriciaTrie<T>>>) -> Vec<&Rc<PatriciaTrie<T>>> {
+    let mut cs: Vec<(&char, &Rc<PatriciaTrie<T>>)> = children.iter().collect();
+    cs.sort_by(|a, b| b.0.cmp(a.0));
+    cs.into_iter().map(|c| c.1).collect()
+}
+
+impl<T> PatriciaTrie<T> {
+    /// Iterates over the bindings of the trie in lexicographic order of their keys.
+    pub fn iter<'a>(&'a self) -> Iter<'a, T> {
+        Iter { stack: vec![(String::new(), self)] }
+    }
+}
+
+pub struct Iter<'a, T: 'a> {
+    stack: Vec<(String, &'a PatriciaTrie<T>)>
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (String, &'a T);
+
+    fn next(&mut self) -> Option<(String, &'a T)> {
+        while let Some((prefix, t)) = self.stack.pop() {
+            if let Node { ref key, ref value, ref children } = *t {
+                let k = prefix + key;
+
+                for c in sorted_children(children) {
+                    self.stack.push((k.clone(), c));
+                }
+
+                if let Some(ref v) = *value {
+                    return Some((k, v));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+pub struct IntoIter<T> {
+    stack: Vec<(String, Rc<PatriciaTrie<T>>)>
+}
+
+impl<T: Clone> Iterator for IntoIter<T> {
+    type Item = (String, T);
+
+    fn next(&mut self) -> Option<(String, T)> {
+        while let Some((prefix, t)) = self.stack.pop() {
+            if let Node { ref key, ref value, ref children } = *t {
+                let k = prefix + key;
+
+                for c in sorted_children(children) {
+                    self.stack.push((k.clone(), c.clone()));
+                }
+
+                if let Some(ref v) = *value {
+                    return Some((k, v.clone()));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PatriciaTrie<T> {
+    type Item = (String, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T: Clone> IntoIterator for PatriciaTrie<T> {
+    type Item = (String, T);
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { stack: vec![(String::new(), Rc::new(self))] }
+    }
+}
+
+impl<T: Clone> FromIterator<(String, T)> for PatriciaTrie<T> {
+    fn from_iter<I: IntoIterator<Item = (String, T)>>(iter: I) -> PatriciaTrie<T> {
+        iter.into_iter().fold(Map::empty(), |t: PatriciaTrie<T>, (k, v)| t.bind(k, v))
+    }
+}
+
+impl<T: Clone> Extend<(String, T)> for PatriciaTrie<T> {
+    fn extend<I: IntoIterator<Item = (String, T)>>(&mut self, iter: I) {
+        *self = iter.into_iter().fold(self.clone(), |t, (k, v)| t.bind(k, v));
+    }
+}
+
 impl<T: Display> Display for PatriciaTrie<T> {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         fn aux<T: Display>(t: &PatriciaTrie<T>, mut indent: String, last: bool, f: &mut Formatter) -> Result<(), Error> {
@@ -144,6 +298,264 @@ impl<T: Display> Display for PatriciaTrie<T> {
     }
 }
 
+// The bindings are compared in lexicographic order of their keys, as `iter` spells them out, so
+// how the keys happen to be split among the nodes does not matter.
+
+impl<T: PartialEq> PartialEq for PatriciaTrie<T> {
+    fn eq(&self, other: &PatriciaTrie<T>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for PatriciaTrie<T> {}
+
+impl<T: PartialOrd> PartialOrd for PatriciaTrie<T> {
+    fn partial_cmp(&self, other: &PatriciaTrie<T>) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for PatriciaTrie<T> {
+    fn cmp(&self, other: &PatriciaTrie<T>) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for PatriciaTrie<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut n = 0;
+
+        for b in self.iter() {
+            b.hash(state);
+            n += 1;
+        }
+
+        state.write_usize(n);
+    }
+}
+
+// Section 10.3.1: tries over keys that are sequences of components, whose nodes keep their
+// children in a finite map from components to subtries. Which map that is, and with it which keys
+// the trie takes, is up to `M`, so that the same trie works for paths of numbers, byte strings or
+// sequences of tuples alike.
+
+/// The maps a `Trie<Self, T>` can keep its children in, and the keys it takes.
+pub trait Children<T>: Sized {
+    /// The keys of the trie, which are taken apart into their components.
+    type Key: IntoIterator<Item = Self::Component>;
+    type Component: Clone;
+    type Map: Map<Self::Component, Rc<Trie<Self, T>>> + Lookup<Rc<Trie<Self, T>>, Self::Component> + Clone;
+
+    fn is_empty(children: &Self::Map) -> bool;
+
+    /// The children along with their components, in the order of the components.
+    fn children<'a>(children: &'a Self::Map) -> Vec<(&'a Self::Component, &'a Trie<Self, T>)> where T: 'a;
+}
+
+/// Children kept in a `TreeMap`, ordered by `O`.
+pub struct TreeChildren<K, O = Natural>(PhantomData<(K, O)>);
+
+impl<K: IntoIterator, T, O: Order<K::Item>> Children<T> for TreeChildren<K, O> where K::Item: Clone {
+    type Key = K;
+    type Component = K::Item;
+    type Map = TreeMap<K::Item, Rc<Trie<TreeChildren<K, O>, T>>, O>;
+
+    fn is_empty(children: &Self::Map) -> bool {
+        children.min().is_none()
+    }
+
+    fn children<'a>(children: &'a Self::Map) -> Vec<(&'a K::Item, &'a Trie<Self, T>)> where T: 'a {
+        children.iter().map(|(c, t)| (c, &**t)).collect()
+    }
+}
+
+/// Children kept in a `WeightBalancedMap`.
+pub struct WeightBalancedChildren<K>(PhantomData<K>);
+
+impl<K: IntoIterator, T> Children<T> for WeightBalancedChildren<K> where K::Item: Ord + Clone {
+    type Key = K;
+    type Component = K::Item;
+    type Map = WeightBalancedMap<K::Item, Rc<Trie<WeightBalancedChildren<K>, T>>>;
+
+    fn is_empty(children: &Self::Map) -> bool {
+        children.is_empty()
+    }
+
+    fn children<'a>(children: &'a Self::Map) -> Vec<(&'a K::Item, &'a Trie<Self, T>)> where T: 'a {
+        children.iter().map(|(c, t)| (c, &**t)).collect()
+    }
+}
+
+pub struct Trie<M: Children<T>, T> {
+    value: Option<T>,
+    children: M::Map
+}
+
+impl<M: Children<T>, T: Clone> Clone for Trie<M, T> {
+    fn clone(&self) -> Trie<M, T> {
+        Trie { value: self.value.clone(), children: self.children.clone() }
+    }
+}
+
+impl<M: Children<T>, T> Trie<M, T> {
+    pub fn is_empty(&self) -> bool {
+        self.value.is_none() && M::is_empty(&self.children)
+    }
+}
+
+impl<M: Children<T>, T: Clone> Map<M::Key, T> for Trie<M, T> {
+    fn empty() -> Trie<M, T> {
+        Trie { value: None, children: Map::empty() }
+    }
+
+    fn bind(&self, k: M::Key, v: T) -> Trie<M, T> {
+        fn ins<M: Children<T>, T: Clone, I: Iterator<Item = M::Component>>(t: &Trie<M, T>, mut k: I, v: T) -> Trie<M, T> {
+            match k.next() {
+                None => Trie { value: Some(v), children: t.children.clone() },
+                Some(c) => {
+                    let child = match t.children.find(&c) {
+                        Some(t1) => ins(t1, k, v),
+                        None => ins(&Trie { value: None, children: Map::empty() }, k, v)
+                    };
+
+                    Trie { value: t.value.clone(), children: t.children.bind(c, Rc::new(child)) }
+                }
+            }
+        }
+
+        ins(self, k.into_iter(), v)
+    }
+}
+
+// looked up by any sequence that can be walked through by reference, e.g. a slice for `Vec` keys
+impl<M: Children<T>, T: Clone, Q: ?Sized> Lookup<T, Q> for Trie<M, T> where for<'a> &'a Q: IntoIterator<Item = &'a M::Component> {
+    fn find(&self, k: &Q) -> Option<&T> {
+        let mut t = self;
+
+        for c in k {
+            match t.children.find(c) {
+                Some(t1) => t = t1,
+                None => return None
+            }
+        }
+
+        t.value.as_ref()
+    }
+
+    // subtries left without any binding are dropped, so that the trie stays as small as it would
+    // have been without the key
+    fn remove(&self, k: &Q) -> Trie<M, T> {
+        fn del<'a, M: Children<T>, T: Clone, I: Iterator<Item = &'a M::Component>>(t: &Trie<M, T>, mut k: I) -> Trie<M, T>
+            where M::Component: 'a {
+            match k.next() {
+                None => Trie { value: None, children: t.children.clone() },
+                Some(c) =>
+                    match t.children.find(c) {
+                        None => t.clone(),
+                        Some(t1) => {
+                            let t1 = del(t1, k);
+                            let children = if t1.is_empty() {
+                                t.children.remove(c)
+                            } else {
+                                t.children.bind(c.clone(), Rc::new(t1))
+                            };
+
+                            Trie { value: t.value.clone(), children: children }
+                        }
+                    }
+            }
+        }
+
+        del(self, k.into_iter())
+    }
+}
+
+impl<M: Children<T>, T> Trie<M, T> {
+    /// Iterates over the bindings of the trie in lexicographic order of their keys, which are
+    /// spelled out as the sequences of their components.
+    pub fn iter<'a>(&'a self) -> TrieIter<'a, M, T> {
+        TrieIter { stack: vec![(vec![], self)] }
+    }
+}
+
+// a subtrie along with the components of the key that leads to it
+type Subtrie<'a, M, T> = (Vec<<M as Children<T>>::Component>, &'a Trie<M, T>);
+
+pub struct TrieIter<'a, M: Children<T> + 'a, T: 'a> {
+    stack: Vec<Subtrie<'a, M, T>>
+}
+
+impl<'a, M: Children<T>, T> Iterator for TrieIter<'a, M, T> {
+    type Item = (Vec<M::Component>, &'a T);
+
+    fn next(&mut self) -> Option<(Vec<M::Component>, &'a T)> {
+        while let Some((k, t)) = self.stack.pop() {
+            for (c, t1) in M::children(&t.children).into_iter().rev() {
+                let mut k1 = k.clone();
+                k1.push(c.clone());
+                self.stack.push((k1, t1));
+            }
+
+            if let Some(ref v) = t.value {
+                return Some((k, v));
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a, M: Children<T>, T> IntoIterator for &'a Trie<M, T> {
+    type Item = (Vec<M::Component>, &'a T);
+    type IntoIter = TrieIter<'a, M, T>;
+
+    fn into_iter(self) -> TrieIter<'a, M, T> {
+        self.iter()
+    }
+}
+
+// Like the Patricia trie, a trie is shown, compared and hashed by its bindings in the order of
+// `iter`, however many of its subtries are shared.
+
+impl<M: Children<T>, T: Debug> Debug for Trie<M, T> where M::Component: Debug {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<M: Children<T>, T: PartialEq> PartialEq for Trie<M, T> where M::Component: PartialEq {
+    fn eq(&self, other: &Trie<M, T>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<M: Children<T>, T: Eq> Eq for Trie<M, T> where M::Component: Eq {}
+
+impl<M: Children<T>, T: PartialOrd> PartialOrd for Trie<M, T> where M::Component: PartialOrd {
+    fn partial_cmp(&self, other: &Trie<M, T>) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<M: Children<T>, T: Ord> Ord for Trie<M, T> where M::Component: Ord {
+    fn cmp(&self, other: &Trie<M, T>) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<M: Children<T>, T: Hash> Hash for Trie<M, T> where M::Component: Hash {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut n = 0;
+
+        for b in self.iter() {
+            b.hash(state);
+            n += 1;
+        }
+
+        state.write_usize(n);
+    }
+}
+
 #[test]
 fn patricia_trie() {
     let t: PatriciaTrie<usize> = Map::empty();
@@ -156,12 +568,175 @@ fn patricia_trie() {
         .bind("toast".to_string(), 6)
         .bind("toad".to_string(), 7);
 
-    assert_eq!(t2.lookup("test".to_string()), 0);
-    assert_eq!(t2.lookup("slow".to_string()), 1);
-    assert_eq!(t2.lookup("water".to_string()), 2);
-    assert_eq!(t2.lookup("slower".to_string()), 3);
-    assert_eq!(t2.lookup("tester".to_string()), 4);
-    assert_eq!(t2.lookup("te".to_string()), 5);
-    assert_eq!(t2.lookup("toast".to_string()), 6);
-    assert_eq!(t2.lookup("toad".to_string()), 7);
+    assert_eq!(t2.lookup("test"), Some(0));
+    assert_eq!(t2.lookup("slow"), Some(1));
+    assert_eq!(t2.lookup("water"), Some(2));
+    assert_eq!(t2.lookup("slower"), Some(3));
+    assert_eq!(t2.lookup("tester"), Some(4));
+    assert_eq!(t2.lookup("te"), Some(5));
+    assert_eq!(t2.lookup("toast"), Some(6));
+    assert_eq!(t2.lookup("toad"), Some(7));
+
+    assert_eq!(t.lookup("test"), None);
+    assert_eq!(t2.lookup("t"), None);
+    assert_eq!(t2.lookup("tea"), None);
+    assert_eq!(t2.lookup("testers"), None);
+
+    let t3 = t2.unbind("te")
+        .unbind("slow")
+        .unbind("toad")
+        .unbind("tea");
+
+    assert_eq!(t3.lookup("te"), None);
+    assert_eq!(t3.lookup("slow"), None);
+    assert_eq!(t3.lookup("toad"), None);
+    assert_eq!(t3.lookup("test"), Some(0));
+    assert_eq!(t3.lookup("slower"), Some(3));
+    assert_eq!(t3.lookup("tester"), Some(4));
+    assert_eq!(t3.lookup("toast"), Some(6));
+    assert_eq!(t2.lookup("te"), Some(5));
+
+    let t4 = ["test", "slower", "water", "tester", "toast"].iter()
+        .fold(t3, |t, k| t.unbind(*k));
+
+    match t4 {
+        Tip => (),
+        _ => panic!("expected an empty trie")
+    }
+
+    let t5 = t2.bind("test".to_string(), 8)
+        .insert_with("slow".to_string(), 10, |new, old| new + old)
+        .adjust("water".to_string(), |v| v * 10)
+        .alter("toad".to_string(), |_| None)
+        .alter("tea".to_string(), |_| Some(9));
+
+    assert_eq!(t5.lookup("test"), Some(8));
+    assert_eq!(t5.lookup("slow"), Some(11));
+    assert_eq!(t5.lookup("water"), Some(20));
+    assert_eq!(t5.lookup("toad"), None);
+    assert_eq!(t5.lookup("tea"), Some(9));
+    assert_eq!(t5.lookup("te"), Some(5));
+}
+
+#[test]
+fn patricia_trie_iter() {
+    let keys = vec!["test", "slow", "water", "slower", "tester", "te", "toast", "toad", ""];
+    let t: PatriciaTrie<usize> = keys.iter().enumerate().map(|(i, k)| (k.to_string(), i)).collect();
+
+    assert_eq!(t.iter().map(|(k, v)| (k, *v)).collect::<Vec<(String, usize)>>(),
+               vec![("".to_string(), 8),
+                    ("slow".to_string(), 1),
+                    ("slower".to_string(), 3),
+                    ("te".to_string(), 5),
+                    ("test".to_string(), 0),
+                    ("tester".to_string(), 4),
+                    ("toad".to_string(), 7),
+                    ("toast".to_string(), 6),
+                    ("water".to_string(), 2)]);
+
+    assert_eq!((&t).into_iter().count(), 9);
+    assert_eq!(t.clone().into_iter().map(|(_, v)| v).collect::<Vec<usize>>(), vec![8, 1, 3, 5, 0, 4, 7, 6, 2]);
+
+    let mut t2 = t.clone();
+    t2.extend(vec![("tea".to_string(), 9)]);
+
+    assert_eq!(t2.iter().map(|(k, _)| k).nth(4), Some("tea".to_string()));
+
+    let t3: PatriciaTrie<usize> = Map::empty();
+
+    assert_eq!(t3.iter().count(), 0);
+
+    // binding the keys in another order splits them differently among the nodes
+    let t4: PatriciaTrie<usize> = keys.iter().enumerate().rev().fold(Map::empty(), |t, (i, k)| t.bind(k.to_string(), i));
+
+    assert_eq!(t4, t);
+    assert!(t4 != t2);
+    assert!(t2 < t);
+    assert!(t3 < t);
+}
+
+#[test]
+fn trie() {
+    use order::Reverse;
+
+    let t: Trie<TreeChildren<Vec<u32>>, &str> = Map::empty();
+    let t2 = t.bind(vec![1, 2, 3], "a")
+        .bind(vec![1, 2], "b")
+        .bind(vec![4], "c")
+        .bind(vec![], "d");
+
+    assert_eq!(t2.lookup(&vec![1, 2, 3]), Some("a"));
+    assert_eq!(t2.lookup(&[1, 2][..]), Some("b"));
+    assert_eq!(t2.lookup(&[4][..]), Some("c"));
+    assert_eq!(t2.lookup(&[][..]), Some("d"));
+    assert_eq!(t2.lookup(&[1][..]), None);
+    assert_eq!(t2.lookup(&[1, 2, 3, 4][..]), None);
+    assert_eq!(t.lookup(&[][..]), None);
+
+    let t3 = t2.unbind(&[1, 2][..]).unbind(&[4][..]).unbind(&[5][..]);
+
+    assert_eq!(t3.lookup(&[1, 2, 3][..]), Some("a"));
+    assert_eq!(t3.lookup(&[1, 2][..]), None);
+    assert_eq!(t3.lookup(&[4][..]), None);
+    assert_eq!(t2.lookup(&[1, 2][..]), Some("b"));
+
+    assert!(t.bind(vec![5, 6], "e").unbind(&[5, 6][..]).is_empty());
+    assert!(t3.unbind(&[1, 2, 3][..]).unbind(&[][..]).is_empty());
+
+    let t4 = t2.insert_with(vec![4], "e", |_, old| old).alter(vec![1, 2], |_| None);
+
+    assert_eq!(t4.lookup(&[4][..]), Some("c"));
+    assert_eq!(t4.lookup(&[1, 2][..]), None);
+
+    // byte strings, and tuple paths whose children are kept in reverse order
+    let b: Trie<WeightBalancedChildren<Vec<u8>>, usize> = Map::empty();
+    let b2 = vec!["test", "slow", "slower", "te"].into_iter().enumerate()
+        .fold(b, |b, (i, k)| b.bind(k.as_bytes().to_vec(), i));
+
+    assert_eq!(b2.lookup(&b"slower"[..]), Some(2));
+    assert_eq!(b2.lookup(&b"te"[..]), Some(3));
+    assert_eq!(b2.lookup(&b"tes"[..]), None);
+
+    let p: Trie<TreeChildren<Vec<(char, u32)>, Reverse>, usize> = Map::empty();
+    let p2 = p.bind(vec![('a', 1), ('b', 2)], 0).bind(vec![('a', 1)], 1);
+
+    assert_eq!(p2.lookup(&[('a', 1), ('b', 2)][..]), Some(0));
+    assert_eq!(p2.lookup(&vec![('a', 1)]), Some(1));
+    assert_eq!(p2.lookup(&[('a', 2)][..]), None);
+}
+
+#[test]
+fn trie_iter() {
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash<T: Hash>(x: &T) -> u64 {
+        let mut h = DefaultHasher::new();
+        x.hash(&mut h);
+        h.finish()
+    }
+
+    let keys = [vec![1, 2, 3], vec![1, 2], vec![4], vec![], vec![1, 3]];
+    let t: Trie<TreeChildren<Vec<u32>>, usize> = keys.iter().enumerate()
+        .fold(Map::empty(), |t, (i, k)| t.bind(k.clone(), i));
+
+    assert_eq!(t.iter().map(|(k, v)| (k, *v)).collect::<Vec<(Vec<u32>, usize)>>(),
+               vec![(vec![], 3), (vec![1, 2], 1), (vec![1, 2, 3], 0), (vec![1, 3], 4), (vec![4], 2)]);
+    assert_eq!((&t).into_iter().count(), 5);
+    assert_eq!(format!("{:?}", t.unbind(&[1, 2, 3][..]).unbind(&[][..]).unbind(&[1, 3][..])), "{[1, 2]: 1, [4]: 2}");
+
+    // binding the keys in another order, or binding and then removing one, gives the same trie
+    let t2: Trie<TreeChildren<Vec<u32>>, usize> = keys.iter().enumerate().rev()
+        .fold(Map::empty(), |t, (i, k)| t.bind(k.clone(), i));
+    let t3 = t.bind(vec![5, 6], 5).unbind(&[5, 6][..]);
+
+    assert_eq!(t2, t);
+    assert_eq!(t3, t);
+    assert_eq!(hash(&t2), hash(&t));
+    assert!(t.unbind(&[4][..]) < t);
+    assert!(t.bind(vec![1, 2], 9) > t);
+
+    let b: Trie<WeightBalancedChildren<Vec<u8>>, usize> = vec!["te", "slow", "test"].into_iter().enumerate()
+        .fold(Map::empty(), |b, (i, k)| b.bind(k.as_bytes().to_vec(), i));
+
+    assert_eq!(b.iter().map(|(k, _)| k).collect::<Vec<Vec<u8>>>(), vec![b"slow".to_vec(), b"te".to_vec(), b"test".to_vec()]);
 }
@@ -0,0 +1,895 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::cmp::Ordering::{Equal, Greater, Less};
+use std::fmt::{Display, Error, Formatter};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use map::{Lookup, Map};
+use order::{Natural, Order};
+use set::{Join, Set};
+
+// Adams, "Efficient sets: a balancing act" (1993): every node stores the size of its subtree, and
+// the sizes of sibling subtrees are kept within a constant factor of each other by single and
+// double rotations. Besides keeping the tree O(log n) high, the sizes give the rank of an element
+// and the element at a given position in O(log n) time.
+
+// the tip carries the ordering of the tree, like it does for Tree
+#[derive(Debug)]
+pub enum WeightBalancedTree<T, O = Natural> {
+    Tip(PhantomData<O>),
+    Node(usize, Rc<WeightBalancedTree<T, O>>, T, Rc<WeightBalancedTree<T, O>>),
+}
+
+use weight_balanced_tree::WeightBalancedTree::{Tip, Node};
+
+impl<T: Clone, O> Clone for WeightBalancedTree<T, O> {
+    fn clone(&self) -> WeightBalancedTree<T, O> {
+        match *self {
+            Tip(_) => Tip(PhantomData),
+            Node(s, ref l, ref x, ref r) => Node(s, l.clone(), x.clone(), r.clone())
+        }
+    }
+}
+
+// The parameters are the ones that Hirai and Yamamoto, "Balancing weight-balanced trees" (2011),
+// proved to be correct: the weight of a subtree, i.e. its size plus one, may be at most DELTA
+// times the weight of its sibling, and a double rotation is used when the inner grandchild
+// weighs at least RATIO times the outer one.
+const DELTA: usize = 3;
+const RATIO: usize = 2;
+
+impl<T: Display, O> Display for WeightBalancedTree<T, O> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        fn aux<T: Display, O>(f: &mut Formatter, t: &WeightBalancedTree<T, O>, right: bool, indent: &str) -> Result<(), Error> {
+            match *t {
+                Node(s, ref l, ref x, ref r) => {
+                    try!(aux(f, r, true, &(indent.to_string() + if right { "        " } else { " |      " })));
+
+                    try!(write!(f, "{}", indent));
+                    try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
+                    try!(write!(f, "{}", "----- "));
+
+                    try!(writeln!(f, "(#{}, {})", s, x));
+
+                    aux(f, l, false, &(indent.to_string() + if right { " |      " } else { "        " }))
+                },
+                Tip(_) => {
+                    try!(write!(f, "{}", indent));
+                    try!(if right { write!(f, "{}", " /") } else { write!(f, "{}", " \\") });
+                    try!(write!(f, "{}", "----- "));
+
+                    writeln!(f, "{}", "()")
+                }
+            }
+        }
+
+        match *self {
+            Node(s, ref l, ref x, ref r) => {
+                try!(aux(f, r, true, ""));
+                try!(writeln!(f, "(#{}, {})", s, x));
+                aux(f, l, false, "")
+            },
+            Tip(_) => Result::Ok(())
+        }
+    }
+}
+
+// The elements are compared in order. The sizes only depend on the shape, so they are left out.
+
+impl<T: PartialEq, O> PartialEq for WeightBalancedTree<T, O> {
+    fn eq(&self, other: &WeightBalancedTree<T, O>) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, O> Eq for WeightBalancedTree<T, O> {}
+
+impl<T: PartialOrd, O> PartialOrd for WeightBalancedTree<T, O> {
+    fn partial_cmp(&self, other: &WeightBalancedTree<T, O>) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord, O> Ord for WeightBalancedTree<T, O> {
+    fn cmp(&self, other: &WeightBalancedTree<T, O>) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash, O> Hash for WeightBalancedTree<T, O> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for x in self.iter() {
+            x.hash(state);
+        }
+
+        state.write_usize(self.len());
+    }
+}
+
+fn size<T, O>(t: &WeightBalancedTree<T, O>) -> usize {
+    match *t {
+        Tip(_) => 0,
+        Node(s, _, _, _) => s
+    }
+}
+
+fn weight<T, O>(t: &WeightBalancedTree<T, O>) -> usize {
+    size(t) + 1
+}
+
+fn node<T, O>(l: Rc<WeightBalancedTree<T, O>>, x: T, r: Rc<WeightBalancedTree<T, O>>) -> WeightBalancedTree<T, O> {
+    Node(size(&l) + size(&r) + 1, l, x, r)
+}
+
+// restores the balance of a node after one of its subtrees grew or shrank by one element, or
+// after `link` rebalanced one of them
+fn balance<T: Clone, O>(l: Rc<WeightBalancedTree<T, O>>, x: T, r: Rc<WeightBalancedTree<T, O>>) -> WeightBalancedTree<T, O> {
+    if weight(&r) > DELTA * weight(&l) {
+        match *r {
+            Node(_, ref rl, ref y, ref rr) =>
+                if weight(rl) < RATIO * weight(rr) {
+                    node(Rc::new(node(l, x, rl.clone())), y.clone(), rr.clone())
+                } else {
+                    match **rl {
+                        Node(_, ref rll, ref z, ref rlr) =>
+                            node(Rc::new(node(l, x, rll.clone())), z.clone(), Rc::new(node(rlr.clone(), y.clone(), rr.clone()))),
+                        Tip(_) => unreachable!()
+                    }
+                },
+            Tip(_) => unreachable!()
+        }
+    } else if weight(&l) > DELTA * weight(&r) {
+        match *l {
+            Node(_, ref ll, ref y, ref lr) =>
+                if weight(lr) < RATIO * weight(ll) {
+                    node(ll.clone(), y.clone(), Rc::new(node(lr.clone(), x, r)))
+                } else {
+                    match **lr {
+                        Node(_, ref lrl, ref z, ref lrr) =>
+                            node(Rc::new(node(ll.clone(), y.clone(), lrl.clone())), z.clone(), Rc::new(node(lrr.clone(), x, r))),
+                        Tip(_) => unreachable!()
+                    }
+                },
+            Tip(_) => unreachable!()
+        }
+    } else {
+        node(l, x, r)
+    }
+}
+
+fn insert_min<T: Clone, O>(x: T, t: &WeightBalancedTree<T, O>) -> WeightBalancedTree<T, O> {
+    match *t {
+        Tip(_) => node(Rc::new(Tip(PhantomData)), x, Rc::new(Tip(PhantomData))),
+        Node(_, ref l, ref y, ref r) => balance(Rc::new(insert_min(x, l)), y.clone(), r.clone())
+    }
+}
+
+fn insert_max<T: Clone, O>(x: T, t: &WeightBalancedTree<T, O>) -> WeightBalancedTree<T, O> {
+    match *t {
+        Tip(_) => node(Rc::new(Tip(PhantomData)), x, Rc::new(Tip(PhantomData))),
+        Node(_, ref l, ref y, ref r) => balance(l.clone(), y.clone(), Rc::new(insert_max(x, r)))
+    }
+}
+
+// builds a balanced tree out of two trees of any size and an element between them, by descending
+// the taller one until both sides are balanced
+fn link<T: Clone, O>(l: &Rc<WeightBalancedTree<T, O>>, x: T, r: &Rc<WeightBalancedTree<T, O>>) -> WeightBalancedTree<T, O> {
+    match (&**l, &**r) {
+        (&Tip(_), _) => insert_min(x, r),
+        (_, &Tip(_)) => insert_max(x, l),
+        (&Node(_, ref ll, ref y, ref lr), &Node(_, ref rl, ref z, ref rr)) =>
+            if DELTA * weight(l) < weight(r) {
+                balance(Rc::new(link(l, x, rl)), z.clone(), rr.clone())
+            } else if DELTA * weight(r) < weight(l) {
+                balance(ll.clone(), y.clone(), Rc::new(link(lr, x, r)))
+            } else {
+                node(l.clone(), x, r.clone())
+            }
+    }
+}
+
+fn remove_min<T: Clone, O>(l: &Rc<WeightBalancedTree<T, O>>, x: &T, r: &Rc<WeightBalancedTree<T, O>>) -> (T, WeightBalancedTree<T, O>) {
+    match **l {
+        Tip(_) => (x.clone(), (**r).clone()),
+        Node(_, ref ll, ref y, ref lr) => {
+            let (m, l1) = remove_min(ll, y, lr);
+            (m, balance(Rc::new(l1), x.clone(), r.clone()))
+        }
+    }
+}
+
+fn remove_max<T: Clone, O>(l: &Rc<WeightBalancedTree<T, O>>, x: &T, r: &Rc<WeightBalancedTree<T, O>>) -> (T, WeightBalancedTree<T, O>) {
+    match **r {
+        Tip(_) => (x.clone(), (**l).clone()),
+        Node(_, ref rl, ref y, ref rr) => {
+            let (m, r1) = remove_max(rl, y, rr);
+            (m, balance(l.clone(), x.clone(), Rc::new(r1)))
+        }
+    }
+}
+
+// joins the two subtrees of a deleted node, taking the new root from the bigger one
+fn glue<T: Clone, O>(l: &Rc<WeightBalancedTree<T, O>>, r: &Rc<WeightBalancedTree<T, O>>) -> WeightBalancedTree<T, O> {
+    match (&**l, &**r) {
+        (&Tip(_), _) => (**r).clone(),
+        (_, &Tip(_)) => (**l).clone(),
+        (&Node(sl, ref ll, ref x, ref lr), &Node(sr, ref rl, ref y, ref rr)) =>
+            if sl > sr {
+                let (m, l1) = remove_max(ll, x, lr);
+                balance(Rc::new(l1), m, r.clone())
+            } else {
+                let (m, r1) = remove_min(rl, y, rr);
+                balance(l.clone(), m, Rc::new(r1))
+            }
+    }
+}
+
+impl<T, O> WeightBalancedTree<T, O> {
+    /// The number of elements, in O(1) time.
+    pub fn len(&self) -> usize {
+        size(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        size(self) == 0
+    }
+
+    /// The element at position `i` in order, counting from zero.
+    pub fn select(&self, i: usize) -> Option<&T> {
+        let mut t = self;
+        let mut i = i;
+
+        while let Node(_, ref l, ref x, ref r) = *t {
+            match i.cmp(&size(l)) {
+                Less => t = l,
+                Equal => return Some(x),
+                Greater => {
+                    i -= size(l) + 1;
+                    t = r;
+                }
+            }
+        }
+
+        None
+    }
+
+    // the number of elements for which `f` answers Less, i.e. that come before the element f
+    // compares them to
+    fn rank_by<F: Fn(&T) -> Ordering>(&self, f: F) -> usize {
+        let mut t = self;
+        let mut n = 0;
+
+        while let Node(_, ref l, ref x, ref r) = *t {
+            match f(x) {
+                Less => {
+                    n += size(l) + 1;
+                    t = r;
+                },
+                Equal => return n + size(l),
+                Greater => t = l
+            }
+        }
+
+        n
+    }
+
+    /// Checks that every node stores the size of its subtree and that no subtree weighs more
+    /// than DELTA times its sibling.
+    pub fn check_invariants(&self) -> bool {
+        match *self {
+            Tip(_) => true,
+            Node(s, ref l, _, ref r) =>
+                s == size(l) + size(r) + 1 && weight(l) <= DELTA * weight(r) && weight(r) <= DELTA * weight(l) &&
+                    l.check_invariants() && r.check_invariants()
+        }
+    }
+}
+
+impl<T, O> WeightBalancedTree<T, O> {
+    /// The number of elements smaller than `x`, which is the position of `x` if it is present.
+    pub fn rank<Q: ?Sized>(&self, x: &Q) -> usize where T: Borrow<Q>, O: Order<Q> {
+        self.rank_by(|y| O::compare(y.borrow(), x))
+    }
+}
+
+impl<T: Clone, O> WeightBalancedTree<T, O> {
+    /// The first `n` elements in order.
+    pub fn take(&self, n: usize) -> WeightBalancedTree<T, O> {
+        match *self {
+            Tip(_) => Tip(PhantomData),
+            Node(s, _, _, _) if n >= s => self.clone(),
+            Node(_, ref l, ref x, ref r) =>
+                if n <= size(l) {
+                    l.take(n)
+                } else {
+                    link(l, x.clone(), &Rc::new(r.take(n - size(l) - 1)))
+                }
+        }
+    }
+
+    /// All but the first `n` elements in order.
+    pub fn drop(&self, n: usize) -> WeightBalancedTree<T, O> {
+        match *self {
+            _ if n == 0 => self.clone(),
+            Tip(_) => Tip(PhantomData),
+            Node(s, _, _, _) if n >= s => Tip(PhantomData),
+            Node(_, ref l, ref x, ref r) =>
+                if n > size(l) {
+                    WeightBalancedTree::drop(r, n - size(l) - 1)
+                } else {
+                    link(&Rc::new(WeightBalancedTree::drop(l, n)), x.clone(), r)
+                }
+        }
+    }
+
+    /// The first `i` elements and the remaining ones, i.e. `(self.take(i), self.drop(i))`.
+    pub fn split_at(&self, i: usize) -> (WeightBalancedTree<T, O>, WeightBalancedTree<T, O>) {
+        match *self {
+            Tip(_) => (Tip(PhantomData), Tip(PhantomData)),
+            Node(s, _, _, _) if i >= s => (self.clone(), Tip(PhantomData)),
+            Node(_, ref l, ref x, ref r) =>
+                if i <= size(l) {
+                    let (ll, lr) = l.split_at(i);
+                    (ll, link(&Rc::new(lr), x.clone(), r))
+                } else {
+                    let (rl, rr) = r.split_at(i - size(l) - 1);
+                    (link(l, x.clone(), &Rc::new(rl)), rr)
+                }
+        }
+    }
+}
+
+impl<T: Clone, O: Order<T>> Set<T> for WeightBalancedTree<T, O> {
+    fn empty() -> WeightBalancedTree<T, O> {
+        Tip(PhantomData)
+    }
+
+    fn insert(&self, x: T) -> WeightBalancedTree<T, O> {
+        match *self {
+            Tip(_) => node(Rc::new(Tip(PhantomData)), x, Rc::new(Tip(PhantomData))),
+            Node(_, ref l, ref y, ref r) if O::lt(&x, y) => balance(Rc::new(l.insert(x)), y.clone(), r.clone()),
+            Node(_, ref l, ref y, ref r) if O::lt(y, &x) => balance(l.clone(), y.clone(), Rc::new(r.insert(x))),
+            _ => self.clone()
+        }
+    }
+}
+
+impl<T: Clone + Borrow<Q>, Q: ?Sized, O: Order<Q>> Lookup<T, Q> for WeightBalancedTree<T, O> {
+    fn find(&self, x: &Q) -> Option<&T> {
+        match *self {
+            Tip(_) => None,
+            Node(_, ref l, ref y, _) if O::lt(x, y.borrow()) => l.find(x),
+            Node(_, _, ref y, ref r) if O::lt(y.borrow(), x) => r.find(x),
+            Node(_, _, ref y, _) => Some(y)
+        }
+    }
+
+    fn remove(&self, x: &Q) -> WeightBalancedTree<T, O> {
+        match *self {
+            Tip(_) => Tip(PhantomData),
+            Node(_, ref l, ref y, ref r) if O::lt(x, y.borrow()) => balance(Rc::new(l.remove(x)), y.clone(), r.clone()),
+            Node(_, ref l, ref y, ref r) if O::lt(y.borrow(), x) => balance(l.clone(), y.clone(), Rc::new(r.remove(x))),
+            Node(_, ref l, _, ref r) => glue(l, r)
+        }
+    }
+}
+
+impl<T: Clone, O: Order<T>> Join<T> for WeightBalancedTree<T, O> {
+    fn expose(&self) -> Option<(WeightBalancedTree<T, O>, T, WeightBalancedTree<T, O>)> {
+        match *self {
+            Tip(_) => None,
+            Node(_, ref l, ref x, ref r) => Some(((**l).clone(), x.clone(), (**r).clone()))
+        }
+    }
+
+    fn split(&self, x: &T) -> (WeightBalancedTree<T, O>, bool, WeightBalancedTree<T, O>) {
+        match *self {
+            Tip(_) => (Tip(PhantomData), false, Tip(PhantomData)),
+            Node(_, ref l, ref y, ref r) if O::lt(x, y) => {
+                let (ll, present, lr) = l.split(x);
+                (ll, present, link(&Rc::new(lr), y.clone(), r))
+            },
+            Node(_, ref l, ref y, ref r) if O::lt(y, x) => {
+                let (rl, present, rr) = r.split(x);
+                (link(l, y.clone(), &Rc::new(rl)), present, rr)
+            },
+            Node(_, ref l, _, ref r) => ((**l).clone(), true, (**r).clone())
+        }
+    }
+
+    fn join(&self, x: T, r: &WeightBalancedTree<T, O>) -> WeightBalancedTree<T, O> {
+        link(&Rc::new(self.clone()), x, &Rc::new(r.clone()))
+    }
+
+    fn same(&self, other: &WeightBalancedTree<T, O>) -> bool {
+        match (self, other) {
+            (&Tip(_), &Tip(_)) => true,
+            (&Node(_, ref l1, ref x1, ref r1), &Node(_, ref l2, ref x2, ref r2)) =>
+                Rc::ptr_eq(l1, l2) && Rc::ptr_eq(r1, r2) && O::compare(x1, x2) == Equal,
+            _ => false
+        }
+    }
+}
+
+impl<T, O> WeightBalancedTree<T, O> {
+    /// Iterates over the elements of the tree in order.
+    pub fn iter<'a>(&'a self) -> Iter<'a, T, O> {
+        let mut it = Iter { stack: vec![] };
+        it.push_left(self);
+        it
+    }
+}
+
+pub struct Iter<'a, T: 'a, O: 'a> {
+    stack: Vec<&'a WeightBalancedTree<T, O>>
+}
+
+impl<'a, T, O> Iter<'a, T, O> {
+    fn push_left(&mut self, mut t: &'a WeightBalancedTree<T, O>) {
+        while let Node(_, ref l, _, _) = *t {
+            self.stack.push(t);
+            t = l;
+        }
+    }
+}
+
+impl<'a, T, O> Iterator for Iter<'a, T, O> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        match self.stack.pop() {
+            Some(&Node(_, _, ref x, ref r)) => {
+                self.push_left(r);
+                Some(x)
+            },
+            _ => None
+        }
+    }
+}
+
+pub struct IntoIter<T, O> {
+    stack: Vec<Rc<WeightBalancedTree<T, O>>>
+}
+
+impl<T, O> IntoIter<T, O> {
+    fn push_left(&mut self, mut t: Rc<WeightBalancedTree<T, O>>) {
+        loop {
+            let l = match *t {
+                Node(_, ref l, _, _) => l.clone(),
+                Tip(_) => return
+            };
+
+            self.stack.push(t);
+            t = l;
+        }
+    }
+}
+
+impl<T: Clone, O> Iterator for IntoIter<T, O> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match self.stack.pop() {
+            Some(t) =>
+                match *t {
+                    Node(_, _, ref x, ref r) => {
+                        self.push_left(r.clone());
+                        Some(x.clone())
+                    },
+                    Tip(_) => None
+                },
+            None => None
+        }
+    }
+}
+
+impl<'a, T, O> IntoIterator for &'a WeightBalancedTree<T, O> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, O>;
+
+    fn into_iter(self) -> Iter<'a, T, O> {
+        self.iter()
+    }
+}
+
+impl<T: Clone, O> IntoIterator for WeightBalancedTree<T, O> {
+    type Item = T;
+    type IntoIter = IntoIter<T, O>;
+
+    fn into_iter(self) -> IntoIter<T, O> {
+        let mut it = IntoIter { stack: vec![] };
+        it.push_left(Rc::new(self));
+        it
+    }
+}
+
+impl<T, O> WeightBalancedTree<T, O> {
+    /// Builds a tree out of elements given in order and without duplicates in O(n) time, by
+    /// splitting them evenly.
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> WeightBalancedTree<T, O> {
+        fn build<T, O, I: Iterator<Item = T>>(n: usize, it: &mut I) -> WeightBalancedTree<T, O> {
+            if n == 0 {
+                return Tip(PhantomData);
+            }
+
+            let l = build(n / 2, it);
+            let x = it.next().unwrap();
+            let r = build(n - n / 2 - 1, it);
+
+            Node(n, Rc::new(l), x, Rc::new(r))
+        }
+
+        let xs: Vec<T> = iter.into_iter().collect();
+        build(xs.len(), &mut xs.into_iter())
+    }
+}
+
+impl<T: Clone, O: Order<T>> FromIterator<T> for WeightBalancedTree<T, O> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> WeightBalancedTree<T, O> {
+        let mut xs: Vec<T> = iter.into_iter().collect();
+
+        xs.sort_by(O::compare);
+        xs.dedup_by(|x, y| O::compare(x, y) == Equal);
+
+        WeightBalancedTree::from_sorted_iter(xs)
+    }
+}
+
+impl<T: Clone, O: Order<T>> Extend<T> for WeightBalancedTree<T, O> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        *self = iter.into_iter().fold(self.clone(), |t, x| t.insert(x));
+    }
+}
+
+/// A weight-balanced tree of bindings used as a map. Only the keys are ever compared, by `O`, so
+/// that binding a key again replaces its value, and the positions are those of the keys.
+#[derive(Debug)]
+pub struct WeightBalancedMap<K, V, O = Natural>(WeightBalancedTree<(K, V), O>);
+
+impl<K: Clone, V: Clone, O> Clone for WeightBalancedMap<K, V, O> {
+    fn clone(&self) -> WeightBalancedMap<K, V, O> {
+        WeightBalancedMap(self.0.clone())
+    }
+}
+
+// like the trees, maps are compared and hashed by their bindings in order of their keys
+
+impl<K: PartialEq, V: PartialEq, O> PartialEq for WeightBalancedMap<K, V, O> {
+    fn eq(&self, other: &WeightBalancedMap<K, V, O>) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<K: Eq, V: Eq, O> Eq for WeightBalancedMap<K, V, O> {}
+
+impl<K: PartialOrd, V: PartialOrd, O> PartialOrd for WeightBalancedMap<K, V, O> {
+    fn partial_cmp(&self, other: &WeightBalancedMap<K, V, O>) -> Option<Ordering> {
+        self.0.partial_cmp(&other.0)
+    }
+}
+
+impl<K: Ord, V: Ord, O> Ord for WeightBalancedMap<K, V, O> {
+    fn cmp(&self, other: &WeightBalancedMap<K, V, O>) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl<K: Hash, V: Hash, O> Hash for WeightBalancedMap<K, V, O> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+fn bind_key<K: Clone, V: Clone, O: Order<K>>(t: &WeightBalancedTree<(K, V), O>, k: K, v: V) -> WeightBalancedTree<(K, V), O> {
+    match *t {
+        Tip(_) => node(Rc::new(Tip(PhantomData)), (k, v), Rc::new(Tip(PhantomData))),
+        Node(_, ref l, ref b, ref r) if O::lt(&k, &b.0) => balance(Rc::new(bind_key(l, k, v)), b.clone(), r.clone()),
+        Node(_, ref l, ref b, ref r) if O::lt(&b.0, &k) => balance(l.clone(), b.clone(), Rc::new(bind_key(r, k, v))),
+        Node(s, ref l, _, ref r) => Node(s, l.clone(), (k, v), r.clone())
+    }
+}
+
+fn remove_key<K: Clone + Borrow<Q>, V: Clone, Q: ?Sized, O: Order<Q>>(t: &WeightBalancedTree<(K, V), O>, k: &Q)
+                                                                      -> WeightBalancedTree<(K, V), O> {
+    match *t {
+        Tip(_) => Tip(PhantomData),
+        Node(_, ref l, ref b, ref r) if O::lt(k, b.0.borrow()) => balance(Rc::new(remove_key(l, k)), b.clone(), r.clone()),
+        Node(_, ref l, ref b, ref r) if O::lt(b.0.borrow(), k) => balance(l.clone(), b.clone(), Rc::new(remove_key(r, k))),
+        Node(_, ref l, _, ref r) => glue(l, r)
+    }
+}
+
+impl<K, V, O> WeightBalancedMap<K, V, O> {
+    /// The number of bindings, in O(1) time.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The binding at position `i` in order of the keys, counting from zero.
+    pub fn select(&self, i: usize) -> Option<(&K, &V)> {
+        self.0.select(i).map(|&(ref k, ref v)| (k, v))
+    }
+
+    /// The number of keys smaller than `k`, which is the position of its binding if there is one.
+    pub fn rank<Q: ?Sized>(&self, k: &Q) -> usize where K: Borrow<Q>, O: Order<Q> {
+        self.0.rank_by(|&(ref k1, _)| O::compare(k1.borrow(), k))
+    }
+
+    pub fn check_invariants(&self) -> bool {
+        self.0.check_invariants()
+    }
+
+    /// Iterates over the bindings in order of their keys.
+    pub fn iter<'a>(&'a self) -> MapIter<'a, K, V, O> {
+        MapIter(self.0.iter())
+    }
+
+    /// Builds a map out of bindings given in order of their keys and without duplicate keys, in
+    /// O(n) time.
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> WeightBalancedMap<K, V, O> {
+        WeightBalancedMap(WeightBalancedTree::from_sorted_iter(iter))
+    }
+}
+
+impl<K: Clone, V: Clone, O> WeightBalancedMap<K, V, O> {
+    /// The first `n` bindings in order of their keys.
+    pub fn take(&self, n: usize) -> WeightBalancedMap<K, V, O> {
+        WeightBalancedMap(self.0.take(n))
+    }
+
+    /// All but the first `n` bindings in order of their keys.
+    pub fn drop(&self, n: usize) -> WeightBalancedMap<K, V, O> {
+        WeightBalancedMap(WeightBalancedTree::drop(&self.0, n))
+    }
+
+    pub fn split_at(&self, i: usize) -> (WeightBalancedMap<K, V, O>, WeightBalancedMap<K, V, O>) {
+        let (l, r) = self.0.split_at(i);
+        (WeightBalancedMap(l), WeightBalancedMap(r))
+    }
+}
+
+impl<K: Clone, V: Clone, O: Order<K>> Map<K, V> for WeightBalancedMap<K, V, O> {
+    fn empty() -> WeightBalancedMap<K, V, O> {
+        WeightBalancedMap(Tip(PhantomData))
+    }
+
+    fn bind(&self, k: K, v: V) -> WeightBalancedMap<K, V, O> {
+        WeightBalancedMap(bind_key(&self.0, k, v))
+    }
+}
+
+impl<K: Clone + Borrow<Q>, V: Clone, Q: ?Sized, O: Order<Q>> Lookup<V, Q> for WeightBalancedMap<K, V, O> {
+    fn find(&self, k: &Q) -> Option<&V> {
+        let mut t = &self.0;
+
+        while let Node(_, ref l, (ref k1, ref v), ref r) = *t {
+            match O::compare(k, k1.borrow()) {
+                Less => t = l,
+                Equal => return Some(v),
+                Greater => t = r
+            }
+        }
+
+        None
+    }
+
+    fn remove(&self, k: &Q) -> WeightBalancedMap<K, V, O> {
+        WeightBalancedMap(remove_key(&self.0, k))
+    }
+}
+
+pub struct MapIter<'a, K: 'a, V: 'a, O: 'a>(Iter<'a, (K, V), O>);
+
+impl<'a, K, V, O> Iterator for MapIter<'a, K, V, O> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        self.0.next().map(|&(ref k, ref v)| (k, v))
+    }
+}
+
+impl<'a, K, V, O> IntoIterator for &'a WeightBalancedMap<K, V, O> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = MapIter<'a, K, V, O>;
+
+    fn into_iter(self) -> MapIter<'a, K, V, O> {
+        self.iter()
+    }
+}
+
+impl<K: Clone, V: Clone, O> IntoIterator for WeightBalancedMap<K, V, O> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<(K, V), O>;
+
+    fn into_iter(self) -> IntoIter<(K, V), O> {
+        self.0.into_iter()
+    }
+}
+
+// the last of the bindings of a key is the one kept, as if they had been bound one after the other
+impl<K: Clone, V: Clone, O: Order<K>> FromIterator<(K, V)> for WeightBalancedMap<K, V, O> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> WeightBalancedMap<K, V, O> {
+        let mut bs: Vec<(K, V)> = iter.into_iter().collect();
+
+        bs.reverse();
+        bs.sort_by(|a, b| O::compare(&a.0, &b.0));
+        bs.dedup_by(|a, b| O::compare(&a.0, &b.0) == Equal);
+
+        WeightBalancedMap::from_sorted_iter(bs)
+    }
+}
+
+impl<K: Clone, V: Clone, O: Order<K>> Extend<(K, V)> for WeightBalancedMap<K, V, O> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        *self = iter.into_iter().fold(self.clone(), |m, (k, v)| m.bind(k, v));
+    }
+}
+
+#[test]
+fn weightbalancedtree() {
+    let t: WeightBalancedTree<usize> = Set::empty();
+    let t2 = (0..1000).fold(t.clone(), |t, x| t.insert(x));
+
+    assert!(t2.check_invariants());
+    assert_eq!(t2.len(), 1000);
+    assert!((0..1000).all(|x| t2.member(&x)));
+    assert!(!t2.member(&1000));
+
+    let t3: WeightBalancedTree<usize> = (0..1000).map(|x| (x * 7919) % 1000 * 2).collect();
+
+    assert!(t3.check_invariants());
+    assert_eq!(t3.iter().cloned().collect::<Vec<usize>>(), (0..1000).map(|x| x * 2).collect::<Vec<usize>>());
+
+    let mut t4 = t3.clone();
+    let mut present = vec![true; 1000];
+
+    for x in ::random(1000).take(1500) {
+        t4 = t4.delete(&(x * 2));
+        present[x] = false;
+
+        assert!(t4.check_invariants());
+        assert_eq!(t4.len(), present.iter().filter(|&&p| p).count());
+    }
+
+    assert!((0..1000).all(|x| t4.member(&(x * 2)) == present[x]));
+    assert_eq!(t3.len(), 1000);
+
+    let u: WeightBalancedTree<usize> = (0..600).map(|x| x * 3).collect();
+    let union = t3.union(&u);
+
+    assert!(union.check_invariants() && t3.intersection(&u).check_invariants() && t3.difference(&u).check_invariants());
+    assert_eq!(union.len(), 1000 + 600 - 300);
+    assert_eq!(t3.intersection(&u).iter().cloned().collect::<Vec<usize>>(), (0..300).map(|x| x * 6).collect::<Vec<usize>>());
+    assert!(t3.difference(&u).is_disjoint(&u));
+    assert!(u.is_subset(&union));
+}
+
+#[test]
+fn weightbalancedtree_order_statistics() {
+    use order::Reverse;
+
+    let t: WeightBalancedTree<usize> = (0..1000).map(|x| (x * 7919) % 1000 * 2).collect();
+
+    assert_eq!(t.rank(&0), 0);
+    assert_eq!(t.rank(&500), 250);
+    assert_eq!(t.rank(&501), 251);
+    assert_eq!(t.rank(&5000), 1000);
+
+    assert!((0..1000).all(|i| t.select(i) == Some(&(i * 2))));
+    assert!((0..1000).all(|i| t.rank(t.select(i).unwrap()) == i));
+    assert_eq!(t.select(1000), None);
+
+    for &n in &[0, 1, 2, 3, 10, 333, 500, 999, 1000, 2000] {
+        let (l, r) = t.split_at(n);
+        let m = if n < 1000 { n } else { 1000 };
+
+        assert!(l.check_invariants() && r.check_invariants());
+        assert_eq!(l.iter().collect::<Vec<&usize>>(), t.take(n).iter().collect::<Vec<&usize>>());
+        assert_eq!(r.iter().collect::<Vec<&usize>>(), t.drop(n).iter().collect::<Vec<&usize>>());
+        assert!(t.take(n).check_invariants() && t.drop(n).check_invariants());
+        assert_eq!(l.len(), m);
+        assert_eq!(r.len(), 1000 - m);
+        assert_eq!(l.iter().chain(r.iter()).cloned().collect::<Vec<usize>>(), t.iter().cloned().collect::<Vec<usize>>());
+    }
+
+    // a page of ten elements
+    assert_eq!(t.drop(20).take(10).iter().cloned().collect::<Vec<usize>>(), (20..30).map(|x| x * 2).collect::<Vec<usize>>());
+    assert!(t.drop(20).take(10).check_invariants());
+
+    // positions count from the smallest element in the ordering of the tree
+    let r: WeightBalancedTree<usize, Reverse> = (0..100).collect();
+
+    assert!(r.check_invariants());
+    assert_eq!(r.select(0), Some(&99));
+    assert_eq!(r.rank(&90), 9);
+    assert_eq!(r.take(3).iter().cloned().collect::<Vec<usize>>(), vec![99, 98, 97]);
+}
+
+#[test]
+fn weightbalancedtree_map() {
+    use order::Reverse;
+
+    let m: WeightBalancedMap<&str, usize> = Map::empty();
+    let m2 = m.bind("hello", 0)
+        .bind("world", 1)
+        .bind("foo", 2)
+        .bind("bar", 3);
+
+    assert_eq!(m2.lookup("hello"), Some(0));
+    assert_eq!(m2.lookup("bar"), Some(3));
+    assert_eq!(m2.lookup("baz"), None);
+    assert_eq!(m2.len(), 4);
+    assert_eq!(m2.rank("foo"), 1);
+    assert_eq!(m2.rank("goo"), 2);
+    assert_eq!(m2.select(3), Some((&"world", &1)));
+
+    let m3 = m2.bind("foo", 4).unbind("bar").unbind("baz");
+
+    assert!(m3.check_invariants());
+    assert_eq!(m3.len(), 3);
+    assert_eq!(m3.lookup("foo"), Some(4));
+    assert_eq!(m3.lookup("bar"), None);
+    assert_eq!(m2.lookup("foo"), Some(2));
+
+    let m4 = (0..1000).fold(Map::empty(), |m: WeightBalancedMap<usize, usize>, k| m.bind((k * 7919) % 1000, k));
+    let m5 = (0..500).fold(m4.clone(), |m, k| m.unbind(&(k * 2)));
+
+    assert!(m4.check_invariants() && m5.check_invariants());
+    assert_eq!(m5.len(), 500);
+    assert_eq!(m5.rank(&501), 250);
+    assert!((0..1000).all(|k| m5.lookup(&k).is_some() == (k % 2 == 1)));
+
+    // a key is bound once, to the last value it was given
+    let m6: WeightBalancedMap<usize, usize> = vec![(1, 10), (1, 20), (2, 5)].into_iter().collect();
+
+    assert!(m6.check_invariants());
+    assert_eq!(m6.len(), 2);
+    assert_eq!(m6.lookup(&1), Some(20));
+    assert_eq!(m6.bind(1, 10).bind(1, 30).len(), 2);
+
+    let mut m7 = m6.clone();
+    m7.extend(vec![(2, 6), (3, 7), (2, 8)]);
+
+    assert_eq!(m7.iter().collect::<Vec<(&usize, &usize)>>(), vec![(&1, &20), (&2, &8), (&3, &7)]);
+
+    let m8: WeightBalancedMap<usize, usize, Reverse> = m7.into_iter().collect();
+
+    assert_eq!(m8.select(0), Some((&3, &7)));
+    assert_eq!(m8.rank(&1), 2);
+}
+
+#[test]
+fn weightbalancedtree_bulk() {
+    for n in 0..300 {
+        let t: WeightBalancedTree<usize> = WeightBalancedTree::from_sorted_iter(0..n);
+
+        assert!(t.check_invariants());
+        assert_eq!(t.len(), n);
+        assert!((0..n).all(|i| t.select(i) == Some(&i)));
+    }
+
+    let t: WeightBalancedTree<usize> = (0..1000).map(|x| (x * 7919) % 500).collect();
+
+    assert!(t.check_invariants());
+    assert_eq!(t.len(), 500);
+
+    let m: WeightBalancedMap<usize, &str> = WeightBalancedMap::from_sorted_iter(vec![(1, "a"), (2, "b"), (3, "c")]);
+
+    assert_eq!(m.lookup(&2), Some("b"));
+    assert!(m.bind(4, "d").unbind(&1).check_invariants());
+
+    let u = (0..500).rev().fold(Set::empty(), |t: WeightBalancedTree<usize>, x| t.insert(x));
+
+    assert_eq!(t, u);
+    assert!(t.take(499) < t);
+    assert!(t.drop(1) > t);
+    assert_eq!(m.bind(2, "b"), m);
+    assert!(m.bind(2, "x") > m);
+}